@@ -0,0 +1,177 @@
+use std::sync::LazyLock;
+
+use crate::events::{Event, EventRow};
+use crate::shop::UNIVERSE;
+
+use super::AchievementRow;
+
+#[derive(Clone, Copy)]
+pub struct AchievementDefinition {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub badge: char,
+    pub description: &'static str,
+    pub target: i64,
+    pub coins: i64,
+    pub gems: i64,
+    pub update_fn: fn(&mut AchievementRow, &dyn EventRow, &Event) -> bool,
+}
+
+impl AchievementDefinition {
+    const fn new(id: &'static str, name: &'static str, badge: char) -> Self {
+        Self {
+            id,
+            name,
+            badge,
+            description: "",
+            target: 1,
+            coins: 0,
+            gems: 0,
+            update_fn: |_, _, _| false,
+        }
+    }
+
+    const fn set_description(mut self, description: &'static str) -> Self {
+        self.description = description;
+        self
+    }
+
+    const fn set_target(mut self, target: i64) -> Self {
+        self.target = target;
+        self
+    }
+
+    const fn set_coins(mut self, coins: i64) -> Self {
+        self.coins = coins;
+        self
+    }
+
+    const fn set_gems(mut self, gems: i64) -> Self {
+        self.gems = gems;
+        self
+    }
+
+    const fn set_update_fn(
+        mut self,
+        f: fn(&mut AchievementRow, &dyn EventRow, &Event) -> bool,
+    ) -> Self {
+        self.update_fn = f;
+        self
+    }
+
+    pub fn badge(&self) -> String {
+        format!("{} {}", self.badge, self.name)
+    }
+}
+
+const EDGE_ROLL: AchievementDefinition =
+    AchievementDefinition::new("edgeroll", "On The Edge", '🪙')
+        .set_description("Hit the coinflip edge roll")
+        .set_gems(5)
+        .set_update_fn(|achievement, _, event| {
+            let Event::Game(event) = event else {
+                return false;
+            };
+
+            // A regular win pays out double, only the edge roll pays 1000x
+            if event.game_id != "coinflip" || !event.win || event.payout < event.bet * 1000 {
+                return false;
+            }
+
+            achievement.update_progress(1);
+            true
+        });
+
+const PRESTIGE_5: AchievementDefinition = AchievementDefinition::new("prestige5", "Veteran", '⭐')
+    .set_description("Reach prestige 5")
+    .set_target(5)
+    .set_gems(10)
+    .set_update_fn(|achievement, row, _| {
+        if row.prestige() <= achievement.progress {
+            return false;
+        }
+
+        achievement.progress = row.prestige();
+        true
+    });
+
+const UNIVERSE_OWNER: AchievementDefinition =
+    AchievementDefinition::new("universe", "Master of the Universe", '🌌')
+        .set_description("Own a universe")
+        .set_gems(25)
+        .set_update_fn(|achievement, _, event| {
+            let Event::ShopPurchase(event) = event else {
+                return false;
+            };
+
+            if event.item_id != UNIVERSE.id {
+                return false;
+            }
+
+            achievement.update_progress(1);
+            true
+        });
+
+const TICTACTOE_100: AchievementDefinition =
+    AchievementDefinition::new("tictactoe100", "Grandmaster", '⭕')
+        .set_description("Win 100 tic-tac-toe games")
+        .set_target(100)
+        .set_coins(250_000)
+        .set_update_fn(|achievement, _, event| {
+            let Event::Game(event) = event else {
+                return false;
+            };
+
+            if event.game_id != "tictactoe" || !event.win {
+                return false;
+            }
+
+            achievement.update_progress(1);
+            true
+        });
+
+const HIGH_ROLLER: AchievementDefinition =
+    AchievementDefinition::new("highroller", "High Roller", '💰')
+        .set_description("Win a bet of 1,000,000 coins or more")
+        .set_coins(100_000)
+        .set_update_fn(|achievement, _, event| {
+            let Event::Game(event) = event else {
+                return false;
+            };
+
+            if !event.win || event.bet < 1_000_000 {
+                return false;
+            }
+
+            achievement.update_progress(1);
+            true
+        });
+
+pub struct AchievementRegistry(Vec<AchievementDefinition>);
+
+impl AchievementRegistry {
+    pub fn new(achievements: impl IntoIterator<Item = AchievementDefinition>) -> Self {
+        Self(achievements.into_iter().collect())
+    }
+
+    pub fn get_definition(&self, id: &str) -> Option<AchievementDefinition> {
+        self.0
+            .iter()
+            .find(|achievement| achievement.id == id)
+            .copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AchievementDefinition> {
+        self.0.iter()
+    }
+}
+
+pub static ACHIEVEMENT_REGISTRY: LazyLock<AchievementRegistry> = LazyLock::new(|| {
+    AchievementRegistry::new([
+        EDGE_ROLL,
+        PRESTIGE_5,
+        UNIVERSE_OWNER,
+        TICTACTOE_100,
+        HIGH_ROLLER,
+    ])
+});
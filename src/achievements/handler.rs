@@ -0,0 +1,86 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use sqlx::Database;
+
+use crate::events::{Event, EventRow, Subscriber};
+use crate::ledger::{LedgerManager, LedgerSource};
+
+use super::{ACHIEVEMENT_REGISTRY, AchievementRow, AchievementsManager};
+
+pub struct AchievementHandler;
+
+impl AchievementHandler {
+    pub async fn process_achievements<
+        Db: Database,
+        Manager: AchievementsManager<Db>,
+        Ledger: LedgerManager<Db>,
+    >(
+        conn: &mut Db::Connection,
+        row: &mut dyn EventRow,
+        event: &Event,
+    ) -> sqlx::Result<()> {
+        let user_id = event.user_id();
+
+        let mut achievements = Manager::rows(&mut *conn, user_id).await?;
+
+        let mut changed = Vec::new();
+
+        for definition in ACHIEVEMENT_REGISTRY.iter() {
+            let mut achievement = match achievements
+                .iter()
+                .position(|achievement| achievement.achievement_id == definition.id)
+            {
+                Some(i) => achievements.swap_remove(i),
+                None => AchievementRow::new(user_id, definition.id),
+            };
+
+            if achievement.is_unlocked() {
+                continue;
+            }
+
+            if !(definition.update_fn)(&mut achievement, row, event) {
+                continue;
+            }
+
+            if achievement.progress >= definition.target {
+                achievement.unlock();
+
+                let source = LedgerSource::Achievement(definition.id);
+
+                row.add_coins(definition.coins);
+                Ledger::coins(&mut *conn, user_id, definition.coins, source).await?;
+
+                row.add_gems(definition.gems);
+                Ledger::gems(&mut *conn, user_id, definition.gems, source).await?;
+            }
+
+            changed.push(achievement);
+        }
+
+        if !changed.is_empty() {
+            Manager::save(conn, &changed).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Progresses and unlocks achievements on every event.
+pub struct AchievementsSubscriber<Manager, Ledger>(PhantomData<(Manager, Ledger)>);
+
+#[async_trait]
+impl<Db, Manager, Ledger> Subscriber<Db> for AchievementsSubscriber<Manager, Ledger>
+where
+    Db: Database,
+    Manager: AchievementsManager<Db>,
+    Ledger: LedgerManager<Db>,
+{
+    async fn on_event(
+        conn: &mut Db::Connection,
+        row: &mut dyn EventRow,
+        event: &Event,
+    ) -> sqlx::Result<()> {
+        AchievementHandler::process_achievements::<Db, Manager, Ledger>(conn, row, event).await
+    }
+}
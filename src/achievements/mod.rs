@@ -0,0 +1,57 @@
+mod definitions;
+mod handler;
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use serenity::all::UserId;
+use sqlx::any::AnyQueryResult;
+use sqlx::{Database, FromRow};
+
+pub use definitions::{ACHIEVEMENT_REGISTRY, AchievementDefinition};
+pub use handler::{AchievementHandler, AchievementsSubscriber};
+
+#[async_trait]
+pub trait AchievementsManager<Db: Database> {
+    async fn rows(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Vec<AchievementRow>>;
+
+    async fn save(
+        conn: &mut Db::Connection,
+        rows: &[AchievementRow],
+    ) -> sqlx::Result<AnyQueryResult>;
+}
+
+/// A user's progress towards a single achievement. Once unlocked the
+/// achievement is never progressed or rewarded again.
+#[derive(FromRow)]
+pub struct AchievementRow {
+    pub user_id: i64,
+    pub achievement_id: String,
+    pub progress: i64,
+    pub unlocked_at: Option<NaiveDateTime>,
+}
+
+impl AchievementRow {
+    pub fn new(user_id: impl Into<UserId>, achievement_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into().get() as i64,
+            achievement_id: achievement_id.into(),
+            progress: 0,
+            unlocked_at: None,
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked_at.is_some()
+    }
+
+    pub fn update_progress(&mut self, value: i64) {
+        self.progress += value;
+    }
+
+    fn unlock(&mut self) {
+        self.unlocked_at = Some(Utc::now().naive_utc());
+    }
+}
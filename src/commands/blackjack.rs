@@ -0,0 +1,548 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use rand::rng;
+use rand::seq::SliceRandom;
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse, EmojiId,
+    ResolvedOption, ResolvedValue,
+};
+use sqlx::{Database, Pool};
+use zayden_core::{FormatNum, parse_options};
+
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::utils::signed;
+use crate::{
+    ActiveGames, BLANK, CARD_DECK, COIN, Coins, EffectsManager, GameCache, GameManager, GameRow,
+    LedgerManager, LedgerSource, Result, SessionManager, SessionRow, VerifyBet,
+};
+
+use super::Commands;
+
+impl Commands {
+    pub async fn blackjack<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        EffectsHandler: EffectsManager<Db> + Send,
+        GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        interaction.defer(ctx).await.unwrap();
+
+        let mut options = parse_options(options);
+
+        let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
+            unreachable!("bet is required")
+        };
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "blackjack").await?;
+        row.verify_bet(bet)?;
+        row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("blackjack"),
+        )
+        .await?;
+
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
+
+        let mut game = Blackjack::new(bet);
+        let mut session = None;
+
+        if !game.is_finished() {
+            let msg = interaction
+                .edit_response(
+                    ctx,
+                    EditInteractionResponse::new()
+                        .embed(game.embed())
+                        .components(game.components()),
+                )
+                .await?;
+
+            SessionHandler::save(
+                &mut *pool.acquire().await?,
+                SessionRow::new("blackjack", &msg).stake(interaction.user.id, bet),
+            )
+            .await?;
+            session = Some(msg.id);
+
+            let mut stream = msg
+                .await_component_interactions(ctx)
+                .author_id(interaction.user.id)
+                .timeout(Duration::from_secs(120))
+                .stream();
+
+            while let Some(component) = stream.next().await {
+                run_component::<Db, GameHandler, LedgerHandler, SessionHandler>(
+                    ctx, &component, pool, &mut game,
+                )
+                .await?;
+
+                if game.is_finished() {
+                    break;
+                }
+            }
+        }
+
+        game.play_dealer();
+
+        let (total_bet, payout) = game.settle();
+        let win = payout > total_bet;
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        let (payout, effects) =
+            EffectsHandler::payout(&mut *tx, interaction.user.id, total_bet, payout, win).await;
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("blackjack"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Game(
+                    GameEvent::new("blackjack", interaction.user.id, total_bet, win)
+                        .set_payout(payout)
+                        .set_effects(effects),
+                ),
+            )
+            .await?;
+
+        let coins = row.coins();
+
+        GameHandler::save(&mut *tx, row).await?;
+
+        if let Some(id) = session {
+            SessionHandler::delete(&mut *tx, id).await?;
+        }
+
+        tx.commit().await?;
+        GameCache::update(ctx, interaction.user.id).await;
+
+        interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(game.result_embed(total_bet, payout, coins))
+                    .components(Vec::new()),
+            )
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    pub fn register_blackjack() -> CreateCommand {
+        CreateCommand::new("blackjack")
+            .description("Play a game of blackjack against Zayden")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
+                    .required(true),
+            )
+    }
+}
+
+async fn run_component<
+    Db: Database,
+    Manager: GameManager<Db>,
+    Ledger: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    pool: &Pool<Db>,
+    game: &mut Blackjack,
+) -> Result<()> {
+    let custom_id = component.data.custom_id.as_str();
+
+    match custom_id {
+        "bj_hit" => game.hit(),
+        "bj_stand" => game.stand(),
+        "bj_double" | "bj_split" => {
+            let mut tx = pool.begin().await?;
+            let mut row = Manager::row(&mut *tx, component.user.id)
+                .await?
+                .unwrap_or_else(|| GameRow::new(component.user.id));
+
+            let extra = game.current_bet();
+
+            if let Err(e) = row.verify_bet(extra) {
+                component
+                    .create_response(
+                        ctx,
+                        CreateInteractionResponse::Message(
+                            CreateInteractionResponseMessage::new()
+                                .content(e.to_string())
+                                .ephemeral(true),
+                        ),
+                    )
+                    .await?;
+
+                return Ok(());
+            }
+
+            row.bet(extra);
+            Ledger::coins(
+                &mut *tx,
+                component.user.id,
+                -extra,
+                LedgerSource::Game("blackjack"),
+            )
+            .await?;
+
+            Manager::save(&mut *tx, row).await?;
+            SessionHandler::save(
+                &mut *tx,
+                SessionRow::new("blackjack", &component.message)
+                    .stake(component.user.id, game.total_bet() + extra),
+            )
+            .await?;
+            tx.commit().await?;
+
+            if custom_id == "bj_double" {
+                game.double();
+            } else {
+                game.split();
+            }
+        }
+        _ => unreachable!("Invalid custom id"),
+    }
+
+    component
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(game.embed())
+                    .components(game.components()),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn rank(card: EmojiId) -> u8 {
+    let index = CARD_DECK.iter().position(|c| *c == card).unwrap();
+    (index % 13) as u8 + 1
+}
+
+fn card_value(card: EmojiId) -> u8 {
+    match rank(card) {
+        1 => 11,
+        r @ 2..=10 => r,
+        _ => 10,
+    }
+}
+
+fn card_emoji(card: EmojiId) -> String {
+    format!("<:{}:{card}>", rank(card))
+}
+
+struct Hand {
+    cards: Vec<EmojiId>,
+    bet: i64,
+    stood: bool,
+}
+
+impl Hand {
+    fn new(cards: Vec<EmojiId>, bet: i64) -> Self {
+        Self {
+            cards,
+            bet,
+            stood: false,
+        }
+    }
+
+    fn value(&self) -> u8 {
+        let mut aces = self.cards.iter().filter(|card| rank(**card) == 1).count();
+        let mut total = self.cards.iter().copied().map(card_value).sum::<u8>();
+
+        while total > 21 && aces > 0 {
+            total -= 10;
+            aces -= 1;
+        }
+
+        total
+    }
+
+    fn is_blackjack(&self) -> bool {
+        self.cards.len() == 2 && self.value() == 21
+    }
+
+    fn is_bust(&self) -> bool {
+        self.value() > 21
+    }
+
+    fn is_done(&self) -> bool {
+        self.stood || self.value() >= 21
+    }
+
+    fn cards_str(&self) -> String {
+        self.cards
+            .iter()
+            .copied()
+            .map(card_emoji)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+struct Blackjack {
+    deck: Vec<EmojiId>,
+    dealer: Hand,
+    hands: Vec<Hand>,
+    current: usize,
+}
+
+impl Blackjack {
+    fn new(bet: i64) -> Self {
+        let mut deck = CARD_DECK.to_vec();
+        deck.shuffle(&mut rng());
+
+        let player = vec![deck.pop().unwrap(), deck.pop().unwrap()];
+        let dealer = vec![deck.pop().unwrap(), deck.pop().unwrap()];
+
+        let mut game = Self {
+            deck,
+            dealer: Hand::new(dealer, 0),
+            hands: vec![Hand::new(player, bet)],
+            current: 0,
+        };
+
+        if game.dealer.is_blackjack() {
+            game.hands[0].stood = true;
+        }
+
+        game.advance();
+        game
+    }
+
+    fn draw(&mut self) -> EmojiId {
+        self.deck.pop().unwrap()
+    }
+
+    fn advance(&mut self) {
+        while self.current < self.hands.len() && self.hands[self.current].is_done() {
+            self.current += 1;
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.current >= self.hands.len()
+    }
+
+    fn current_bet(&self) -> i64 {
+        self.hands[self.current].bet
+    }
+
+    fn total_bet(&self) -> i64 {
+        self.hands.iter().map(|hand| hand.bet).sum()
+    }
+
+    fn can_double(&self) -> bool {
+        self.hands[self.current].cards.len() == 2
+    }
+
+    fn can_split(&self) -> bool {
+        let hand = &self.hands[self.current];
+
+        self.hands.len() == 1
+            && hand.cards.len() == 2
+            && card_value(hand.cards[0]) == card_value(hand.cards[1])
+    }
+
+    fn hit(&mut self) {
+        let card = self.draw();
+        self.hands[self.current].cards.push(card);
+        self.advance();
+    }
+
+    fn stand(&mut self) {
+        self.hands[self.current].stood = true;
+        self.advance();
+    }
+
+    fn double(&mut self) {
+        let card = self.draw();
+
+        let hand = &mut self.hands[self.current];
+        hand.bet *= 2;
+        hand.cards.push(card);
+        hand.stood = true;
+
+        self.advance();
+    }
+
+    fn split(&mut self) {
+        let first = self.draw();
+        let second = self.draw();
+
+        let hand = &mut self.hands[self.current];
+        let card = hand.cards.pop().unwrap();
+        hand.cards.push(first);
+
+        let new_hand = Hand::new(vec![card, second], hand.bet);
+        self.hands.insert(self.current + 1, new_hand);
+
+        self.advance();
+    }
+
+    fn play_dealer(&mut self) {
+        if self.hands.iter().all(|hand| hand.is_bust()) {
+            return;
+        }
+
+        while self.dealer.value() < 17 {
+            let card = self.draw();
+            self.dealer.cards.push(card);
+        }
+    }
+
+    fn settle(&self) -> (i64, i64) {
+        let natural = self.hands.len() == 1 && self.hands[0].is_blackjack();
+        let dealer = self.dealer.value();
+
+        self.hands.iter().fold((0, 0), |(total_bet, payout), hand| {
+            let hand_payout = if hand.is_bust() {
+                0
+            } else if natural && self.dealer.is_blackjack() {
+                hand.bet
+            } else if natural {
+                hand.bet * 2 + hand.bet / 2
+            } else if self.dealer.is_blackjack() {
+                0
+            } else if dealer > 21 || hand.value() > dealer {
+                hand.bet * 2
+            } else if hand.value() == dealer {
+                hand.bet
+            } else {
+                0
+            };
+
+            (total_bet + hand.bet, payout + hand_payout)
+        })
+    }
+
+    fn hands_embed(&self, reveal: bool) -> CreateEmbed {
+        let dealer = if reveal {
+            format!(
+                "{}\nValue: `{}`",
+                self.dealer.cards_str(),
+                self.dealer.value()
+            )
+        } else {
+            format!(
+                "{} <:blank:{BLANK}>\nValue: `{}`",
+                card_emoji(self.dealer.cards[0]),
+                card_value(self.dealer.cards[0])
+            )
+        };
+
+        let mut embed = CreateEmbed::new().field("Zayden's Hand", dealer, false);
+
+        for (i, hand) in self.hands.iter().enumerate() {
+            let mut name = if self.hands.len() == 1 {
+                String::from("Your Hand")
+            } else {
+                format!("Hand {}", i + 1)
+            };
+
+            if !reveal && i == self.current {
+                name.push_str(" ⬅️");
+            }
+
+            embed = embed.field(
+                name,
+                format!(
+                    "{}\nValue: `{}`\nBet: {} <:coin:{COIN}>",
+                    hand.cards_str(),
+                    hand.value(),
+                    hand.bet.format()
+                ),
+                true,
+            );
+        }
+
+        embed
+    }
+
+    fn embed(&self) -> CreateEmbed {
+        self.hands_embed(false)
+            .title("Blackjack")
+            .colour(Colour::TEAL)
+    }
+
+    fn result_embed(&self, bet: i64, payout: i64, coins: i64) -> CreateEmbed {
+        let (title, colour) = if payout > bet {
+            ("Blackjack - You Won!", Colour::DARK_GREEN)
+        } else if payout == bet {
+            ("Blackjack - Push!", Colour::ORANGE)
+        } else {
+            ("Blackjack - You Lost!", Colour::RED)
+        };
+
+        self.hands_embed(true)
+            .title(title)
+            .description(format!(
+                "Payout: {} ({})\nYour coins: {}",
+                payout.format(),
+                signed(payout - bet),
+                coins.format()
+            ))
+            .colour(colour)
+    }
+
+    fn components(&self) -> Vec<CreateActionRow> {
+        if self.is_finished() {
+            return Vec::new();
+        }
+
+        let hit = CreateButton::new("bj_hit")
+            .label("Hit")
+            .emoji('👊')
+            .style(ButtonStyle::Secondary);
+        let stand = CreateButton::new("bj_stand")
+            .label("Stand")
+            .emoji('✋')
+            .style(ButtonStyle::Secondary);
+        let double = CreateButton::new("bj_double")
+            .label("Double Down")
+            .emoji('💰')
+            .style(ButtonStyle::Secondary)
+            .disabled(!self.can_double());
+        let split = CreateButton::new("bj_split")
+            .label("Split")
+            .emoji('✂')
+            .style(ButtonStyle::Secondary)
+            .disabled(!self.can_split());
+
+        vec![CreateActionRow::Buttons(vec![hit, stand, double, split])]
+    }
+}
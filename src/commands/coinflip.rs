@@ -2,17 +2,18 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 use serenity::all::{
-    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    EditInteractionResponse, ResolvedOption, ResolvedValue,
+    Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    EditInteractionResponse, Mentionable, ResolvedOption, ResolvedValue, UserId,
 };
 use sqlx::{Database, Pool};
-use zayden_core::parse_options;
+use zayden_core::{FormatNum, parse_options};
 
-use crate::events::{Dispatch, Event, GameEvent};
+use crate::duel::Duel;
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
 use crate::utils::{Emoji, GameResult, game_embed};
 use crate::{
-    COIN, Coins, EffectsManager, GameCache, GameManager, GameRow, GoalsManager, Result, TAILS,
-    VerifyBet,
+    ActiveGames, COIN, Coins, EffectsManager, GamblingConfig, GameCache, GameManager, GameRow,
+    LedgerManager, LedgerSource, Result, SessionManager, TAILS, VerifyBet,
 };
 
 use super::Commands;
@@ -20,9 +21,11 @@ use super::Commands;
 impl Commands {
     pub async fn coinflip<
         Db: Database,
-        GoalsHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         EffectsHandler: EffectsManager<Db> + Send,
         GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -42,38 +45,72 @@ impl Commands {
             unreachable!("bet is required")
         };
 
-        let mut row = GameHandler::row(pool, interaction.user.id)
+        if let Some(ResolvedValue::User(opponent, _)) = options.remove("opponent") {
+            return duel::<Db, EventHandler, GameHandler, LedgerHandler, SessionHandler>(
+                ctx,
+                interaction,
+                pool,
+                prediction,
+                opponent.id,
+                bet,
+            )
+            .await;
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
             .await?
             .unwrap_or_else(|| GameRow::new(interaction.user.id));
 
         GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "coinflip").await?;
         row.verify_bet(bet)?;
         row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("coinflip"),
+        )
+        .await?;
 
         let heads = rand::random_bool(0.5);
         let winner = matches!(prediction, CoinSide::Heads) == heads;
         let edge = rand::random_bool(1.0 / 6000.0);
 
-        let mut payout = match (winner, edge) {
+        let payout = match (winner, edge) {
             (true, true) => bet * 1000,
             (true, false) => bet * 2,
             _ => 0,
         };
 
-        Dispatch::<Db, GoalsHandler>::new(pool)
+        let (payout, effects) =
+            EffectsHandler::payout(&mut *tx, interaction.user.id, bet, payout, winner).await;
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("coinflip"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
             .fire(
                 &mut row,
-                Event::Game(GameEvent::new("coinflip", interaction.user.id, bet, winner)),
+                Event::Game(
+                    GameEvent::new("coinflip", interaction.user.id, bet, winner)
+                        .set_payout(payout)
+                        .set_effects(effects),
+                ),
             )
             .await?;
 
-        payout = EffectsHandler::payout(pool, interaction.user.id, bet, payout, winner).await;
-
-        row.add_coins(payout);
-
         let coins = row.coins();
 
-        GameHandler::save(pool, row).await.unwrap();
+        GameHandler::save(&mut *tx, row).await.unwrap();
+        tx.commit().await?;
         GameCache::update(ctx, interaction.user.id).await;
 
         let (coin, title) = if edge {
@@ -119,10 +156,82 @@ impl Commands {
                 CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
                     .required(true),
             )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "opponent",
+                "Challenge another player, who takes the other side of the coin",
+            ))
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+async fn duel<
+    Db: Database,
+    EventHandler: Subscriber<Db>,
+    GameHandler: GameManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    pool: &Pool<Db>,
+    prediction: CoinSide,
+    opponent: UserId,
+    bet: i64,
+) -> Result<()> {
+    let mut duel = Duel::new("coinflip", "Coin Flip", interaction.user.id, opponent, bet)
+        .rake(GamblingConfig::get(ctx).await.duel_rake);
+
+    let terms = format!("a coin flip, calling {prediction}");
+
+    if !duel
+        .challenge::<Db, GameHandler, LedgerHandler, SessionHandler>(ctx, interaction, pool, &terms)
+        .await?
+    {
+        return Ok(());
+    }
+
+    let coin = if rand::random_bool(0.5) {
+        CoinSide::Heads
+    } else {
+        CoinSide::Tails
+    };
+
+    let winner = if coin == prediction {
+        duel.challenger
+    } else {
+        duel.opponent
+    };
+
+    duel.settle::<Db, EventHandler, GameHandler, LedgerHandler, SessionHandler>(
+        ctx,
+        pool,
+        Some(winner),
+    )
+    .await?;
+
+    let embed = duel
+        .embed(format!(
+            "{} called {prediction}\n**Coin landed on:** {} {coin}\n\n{} wins {} <:coin:{COIN}> 🎉",
+            duel.challenger.mention(),
+            GameResult::from(coin).emoji(),
+            winner.mention(),
+            duel.winnings().format()
+        ))
+        .colour(Colour::DARK_GREEN);
+
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CoinSide {
     Heads,
     Tails,
@@ -8,21 +8,29 @@ use sqlx::prelude::FromRow;
 use sqlx::{Database, Pool};
 use zayden_core::{FormatNum, parse_options};
 
+use crate::events::{CraftEvent, Dispatch, Event, Subscriber};
 use crate::shop::ShopCurrency;
-use crate::{Error, Result};
+use crate::{Coins, Error, Gems, MaxBet, Prestige, Result};
 
 use super::Commands;
 
 #[async_trait]
 pub trait CraftManager<Db: Database> {
-    async fn row(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<CraftRow>>;
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<CraftRow>>;
 
-    async fn save(pool: &Pool<Db>, row: CraftRow) -> sqlx::Result<AnyQueryResult>;
+    async fn save(conn: &mut Db::Connection, row: CraftRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(FromRow)]
 pub struct CraftRow {
     pub id: i64,
+    pub coins: i64,
+    pub gems: i64,
+    pub level: Option<i32>,
+    pub prestige: Option<i64>,
     pub coal: i64,
     pub iron: i64,
     pub gold: i64,
@@ -41,6 +49,10 @@ impl CraftRow {
 
         Self {
             id: id.get() as i64,
+            coins: 0,
+            gems: 0,
+            level: Some(0),
+            prestige: Some(0),
             coal: 0,
             iron: 0,
             gold: 0,
@@ -55,8 +67,40 @@ impl CraftRow {
     }
 }
 
+impl Coins for CraftRow {
+    fn coins(&self) -> i64 {
+        self.coins
+    }
+
+    fn coins_mut(&mut self) -> &mut i64 {
+        &mut self.coins
+    }
+}
+
+impl Gems for CraftRow {
+    fn gems(&self) -> i64 {
+        self.gems
+    }
+
+    fn gems_mut(&mut self) -> &mut i64 {
+        &mut self.gems
+    }
+}
+
+impl Prestige for CraftRow {
+    fn prestige(&self) -> i64 {
+        self.prestige.unwrap_or_default()
+    }
+}
+
+impl MaxBet for CraftRow {
+    fn level(&self) -> i32 {
+        self.level.unwrap_or_default()
+    }
+}
+
 impl Commands {
-    pub async fn craft<Db: Database, Manager: CraftManager<Db>>(
+    pub async fn craft<Db: Database, EventHandler: Subscriber<Db>, Manager: CraftManager<Db>>(
         ctx: &Context,
         interaction: &CommandInteraction,
         options: Vec<ResolvedOption<'_>>,
@@ -64,9 +108,10 @@ impl Commands {
     ) -> Result<()> {
         interaction.defer(ctx).await.unwrap();
 
-        let mut row = Manager::row(pool, interaction.user.id)
-            .await
-            .unwrap()
+        let mut tx = pool.begin().await?;
+
+        let mut row = Manager::row(&mut *tx, interaction.user.id)
+            .await?
             .unwrap_or_else(|| CraftRow::new(interaction.user.id));
 
         let mut options = parse_options(options);
@@ -139,7 +184,15 @@ impl Commands {
             c => unreachable!("Invalid item: {c}"),
         };
 
-        Manager::save(pool, row).await.unwrap();
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Craft(CraftEvent::new(interaction.user.id, item, amount)),
+            )
+            .await?;
+
+        Manager::save(&mut *tx, row).await?;
+        tx.commit().await?;
 
         let embed = CreateEmbed::new()
             .description(format!(
@@ -0,0 +1,253 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, Context, CreateButton,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+    EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+use sqlx::{Database, Pool};
+use zayden_core::{FormatNum, parse_options};
+
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::utils::signed;
+use crate::{
+    ActiveGames, COIN, Coins, EffectsManager, GamblingConfig, GameCache, GameManager, GameRow,
+    LedgerManager, LedgerSource, Result, SessionManager, SessionRow, VerifyBet,
+};
+
+use super::Commands;
+
+const GROWTH: f64 = 1.15;
+const MAX_MULTIPLIER: f64 = 100.0;
+const TICK: Duration = Duration::from_secs(2);
+
+impl Commands {
+    pub async fn crash<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        EffectsHandler: EffectsManager<Db> + Send,
+        GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        interaction.defer(ctx).await.unwrap();
+
+        let mut options = parse_options(options);
+
+        let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
+            unreachable!("bet is required")
+        };
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "crash").await?;
+        row.verify_bet(bet)?;
+        row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("crash"),
+        )
+        .await?;
+
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
+
+        let crash_point = crash_point(GamblingConfig::get(ctx).await.crash_house_edge);
+        let mut multiplier = 1.0;
+        let mut cashed_out = false;
+        let mut session = None;
+
+        if crash_point > multiplier {
+            let cash_out = CreateButton::new("crash_cashout")
+                .label("Cash Out")
+                .emoji('💰')
+                .style(ButtonStyle::Success);
+
+            let msg = interaction
+                .edit_response(
+                    ctx,
+                    EditInteractionResponse::new()
+                        .embed(running_embed(bet, multiplier))
+                        .button(cash_out),
+                )
+                .await?;
+
+            SessionHandler::save(
+                &mut *pool.acquire().await?,
+                SessionRow::new("crash", &msg).stake(interaction.user.id, bet),
+            )
+            .await?;
+            session = Some(msg.id);
+
+            let mut stream = msg
+                .await_component_interactions(ctx)
+                .author_id(interaction.user.id)
+                .timeout(Duration::from_secs(120))
+                .stream();
+
+            loop {
+                match tokio::time::timeout(TICK, stream.next()).await {
+                    Ok(Some(component)) => {
+                        component
+                            .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                            .await?;
+
+                        cashed_out = true;
+                        break;
+                    }
+                    Ok(None) => break,
+                    Err(_) => {}
+                }
+
+                let next = (multiplier * GROWTH).min(MAX_MULTIPLIER);
+
+                if next >= crash_point {
+                    multiplier = crash_point;
+                    break;
+                }
+
+                multiplier = next;
+
+                if multiplier >= MAX_MULTIPLIER {
+                    cashed_out = true;
+                    break;
+                }
+
+                interaction
+                    .edit_response(
+                        ctx,
+                        EditInteractionResponse::new().embed(running_embed(bet, multiplier)),
+                    )
+                    .await?;
+            }
+        }
+
+        let payout = if cashed_out {
+            (bet as f64 * multiplier) as i64
+        } else {
+            0
+        };
+        let winner = payout > bet;
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        let (payout, effects) =
+            EffectsHandler::payout(&mut *tx, interaction.user.id, bet, payout, winner).await;
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("crash"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Game(
+                    GameEvent::new("crash", interaction.user.id, bet, winner)
+                        .set_payout(payout)
+                        .set_effects(effects),
+                ),
+            )
+            .await?;
+
+        let coins = row.coins();
+
+        GameHandler::save(&mut *tx, row).await?;
+
+        if let Some(id) = session {
+            SessionHandler::delete(&mut *tx, id).await?;
+        }
+
+        tx.commit().await?;
+        GameCache::update(ctx, interaction.user.id).await;
+
+        let (title, result, colour) = if cashed_out {
+            (
+                "📈 Crash 📈 - You Cashed Out!",
+                format!("Cashed out at **{multiplier:.2}x**"),
+                Colour::DARK_GREEN,
+            )
+        } else {
+            (
+                "📉 Crash 📉 - You Lost!",
+                format!("💥 Crashed at **{crash_point:.2}x**"),
+                Colour::RED,
+            )
+        };
+
+        let embed = CreateEmbed::new()
+            .title(title)
+            .description(format!(
+                "Your bet: {} <:coin:{COIN}>\n\n{result}\n\nPayout: {} ({})\nYour coins: {}",
+                bet.format(),
+                payout.format(),
+                signed(payout - bet),
+                coins.format()
+            ))
+            .colour(colour);
+
+        interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(embed)
+                    .components(Vec::new()),
+            )
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    pub fn register_crash() -> CreateCommand {
+        CreateCommand::new("crash")
+            .description("Cash out before the multiplier crashes")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
+                    .required(true),
+            )
+    }
+}
+
+// P(crash >= m) = (1 - house_edge) / m, so cashing out at any multiplier
+// returns (1 - house_edge) of the bet on average. Points are floored to 2
+// decimals, so everything below 1.01 crashes instantly, a chance of
+// 1 - (1 - house_edge) / 1.01 (~4.95% at a 4% edge).
+fn crash_point(house_edge: f64) -> f64 {
+    let roll = rand::random::<f64>();
+    let point = (1.0 - house_edge) / (1.0 - roll);
+
+    ((point * 100.0).floor() / 100.0).max(1.0)
+}
+
+fn running_embed(bet: i64, multiplier: f64) -> CreateEmbed {
+    let potential = (bet as f64 * multiplier) as i64;
+
+    CreateEmbed::new()
+        .title("📈 Crash 📈")
+        .description(format!(
+            "Your bet: {} <:coin:{COIN}>\n\nMultiplier: **{multiplier:.2}x**\nCash out now for {} <:coin:{COIN}>",
+            bet.format(),
+            potential.format()
+        ))
+        .colour(Colour::TEAL)
+}
@@ -7,22 +7,31 @@ use serenity::all::{
 use sqlx::{Database, Pool, any::AnyQueryResult, prelude::FromRow};
 use zayden_core::FormatNum;
 
-use crate::{COIN, Coins, Error, Result, START_AMOUNT, tomorrow};
+use crate::events::{DailyEvent, Dispatch, Event, Subscriber};
+use crate::{
+    COIN, Coins, Error, Gems, LedgerManager, LedgerSource, MaxBet, Prestige, Result, START_AMOUNT,
+    tomorrow,
+};
 
 use super::Commands;
 
 #[async_trait]
 pub trait DailyManager<Db: Database> {
-    async fn row(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<DailyRow>>;
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<DailyRow>>;
 
-    async fn save(pool: &Pool<Db>, row: DailyRow) -> sqlx::Result<AnyQueryResult>;
+    async fn save(conn: &mut Db::Connection, row: DailyRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(FromRow)]
 pub struct DailyRow {
     pub id: i64,
     pub coins: i64,
+    pub gems: i64,
     pub daily: NaiveDate,
+    pub level: Option<i32>,
     pub prestige: Option<i64>,
 }
 
@@ -33,7 +42,9 @@ impl DailyRow {
         Self {
             id: id.get() as i64,
             coins: 0,
+            gems: 0,
             daily: NaiveDate::default(),
+            level: Some(0),
             prestige: Some(0),
         }
     }
@@ -49,17 +60,45 @@ impl Coins for DailyRow {
     }
 }
 
+impl Gems for DailyRow {
+    fn gems(&self) -> i64 {
+        self.gems
+    }
+
+    fn gems_mut(&mut self) -> &mut i64 {
+        &mut self.gems
+    }
+}
+
+impl Prestige for DailyRow {
+    fn prestige(&self) -> i64 {
+        self.prestige.unwrap_or_default()
+    }
+}
+
+impl MaxBet for DailyRow {
+    fn level(&self) -> i32 {
+        self.level.unwrap_or_default()
+    }
+}
+
 impl Commands {
-    pub async fn daily<Db: Database, Manager: DailyManager<Db>>(
+    pub async fn daily<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        Manager: DailyManager<Db>,
+        Ledger: LedgerManager<Db>,
+    >(
         ctx: &Context,
         interaction: &CommandInteraction,
         pool: &Pool<Db>,
     ) -> Result<()> {
         interaction.defer(ctx).await.unwrap();
 
-        let mut row = Manager::row(pool, interaction.user.id)
-            .await
-            .unwrap()
+        let mut tx = pool.begin().await?;
+
+        let mut row = Manager::row(&mut *tx, interaction.user.id)
+            .await?
             .unwrap_or_else(|| DailyRow::new(interaction.user.id));
 
         let now = Utc::now();
@@ -69,11 +108,20 @@ impl Commands {
             return Err(Error::DailyClaimed(tomorrow(Some(now))));
         }
 
-        let amount = START_AMOUNT * (row.prestige.unwrap_or_default() + 1);
+        let amount = START_AMOUNT * (row.prestige() + 1);
 
         *row.coins_mut() += amount;
 
-        Manager::save(pool, row).await.unwrap();
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Daily(DailyEvent::new(interaction.user.id, amount)),
+            )
+            .await?;
+
+        Ledger::coins(&mut *tx, interaction.user.id, amount, LedgerSource::Daily).await?;
+        Manager::save(&mut *tx, row).await?;
+        tx.commit().await?;
 
         let embed = CreateEmbed::new()
             .description(format!("Collected {} <:coin:{COIN}>", amount.format()))
@@ -11,10 +11,13 @@ use serenity::all::{
 use sqlx::{Database, Pool, any::AnyQueryResult, prelude::FromRow};
 use zayden_core::FormatNum;
 
-use crate::events::{Dispatch, Event};
+use crate::events::{DigEvent, Dispatch, Event, Subscriber};
 use crate::models::{MineAmount, Prestige};
 use crate::shop::ShopCurrency;
-use crate::{COIN, Coins, Gems, GoalsManager, MaxBet, MineHourly, Result, Stamina, StaminaManager};
+use crate::{
+    COIN, Coins, Gems, LedgerManager, LedgerSource, MaxBet, MineHourly, Result, Stamina,
+    StaminaManager,
+};
 
 use super::Commands;
 
@@ -41,9 +44,12 @@ static CHANCES: LazyLock<HashMap<&str, f64>> = LazyLock::new(|| {
 
 #[async_trait]
 pub trait DigManager<Db: Database> {
-    async fn row(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<DigRow>>;
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<DigRow>>;
 
-    async fn save(pool: &Pool<Db>, row: DigRow) -> sqlx::Result<AnyQueryResult>;
+    async fn save(conn: &mut Db::Connection, row: DigRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(Debug, FromRow)]
@@ -169,8 +175,9 @@ impl Commands {
     pub async fn dig<
         Db: Database,
         StaminaHandler: StaminaManager<Db>,
-        GoalsHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         DigHandler: DigManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -178,9 +185,10 @@ impl Commands {
     ) -> Result<()> {
         interaction.defer(ctx).await?;
 
-        let mut row = DigHandler::row(pool, interaction.user.id)
-            .await
-            .unwrap()
+        let mut tx = pool.begin().await?;
+
+        let mut row = DigHandler::row(&mut *tx, interaction.user.id)
+            .await?
             .unwrap_or_else(|| DigRow::new(interaction.user.id));
 
         row.verify_work::<Db, StaminaHandler>()?;
@@ -225,8 +233,11 @@ impl Commands {
             s => unreachable!("Invalid resource: {s}"),
         });
 
-        Dispatch::<Db, GoalsHandler>::new(pool)
-            .fire(&mut row, Event::Work(interaction.user.id))
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Dig(DigEvent::new(interaction.user.id, resources.clone())),
+            )
             .await?;
 
         let mine_amount = row.mine_amount();
@@ -237,7 +248,15 @@ impl Commands {
 
         let stamina = row.stamina_str();
 
-        DigHandler::save(pool, row).await.unwrap();
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            mine_amount,
+            LedgerSource::Dig,
+        )
+        .await?;
+        DigHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
 
         let found = resources
             .drain()
@@ -8,8 +8,8 @@ use sqlx::{Database, Pool, any::AnyQueryResult, prelude::FromRow};
 use zayden_core::FormatNum;
 
 use crate::{
-    Coins, Error, Gems, GoalsManager, MaxBet, Prestige, Result, START_AMOUNT,
-    events::{Dispatch, Event, SendEvent},
+    Coins, Error, Gems, LedgerManager, LedgerSource, MaxBet, Prestige, Result, START_AMOUNT,
+    events::{Dispatch, Event, ReceiveEvent, SendEvent, Subscriber},
     tomorrow,
 };
 
@@ -20,17 +20,30 @@ use super::Commands;
 #[async_trait]
 pub trait GiftManager<Db: Database> {
     async fn sender(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         id: impl Into<UserId> + Send,
     ) -> sqlx::Result<Option<SenderRow>>;
 
     async fn add_coins(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         id: impl Into<UserId> + Send,
         amount: i64,
     ) -> sqlx::Result<AnyQueryResult>;
 
-    async fn save_sender(pool: &Pool<Db>, row: SenderRow) -> sqlx::Result<AnyQueryResult>;
+    /// Must lock the row (`FOR UPDATE`), as it is also read to take the
+    /// recipient's lock before the sender's.
+    async fn recipient(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<RecipientRow>>;
+
+    async fn save_sender(conn: &mut Db::Connection, row: SenderRow)
+    -> sqlx::Result<AnyQueryResult>;
+
+    async fn save_recipient(
+        conn: &mut Db::Connection,
+        row: RecipientRow,
+    ) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(FromRow)]
@@ -94,6 +107,9 @@ impl MaxBet for SenderRow {
 pub struct RecipientRow {
     pub id: i64,
     pub coins: i64,
+    pub gems: i64,
+    pub level: Option<i32>,
+    pub prestige: i64,
 }
 
 impl RecipientRow {
@@ -103,6 +119,9 @@ impl RecipientRow {
         Self {
             id: id.get() as i64,
             coins: 0,
+            gems: 0,
+            level: Some(0),
+            prestige: 0,
         }
     }
 }
@@ -117,11 +136,34 @@ impl Coins for RecipientRow {
     }
 }
 
+impl Gems for RecipientRow {
+    fn gems(&self) -> i64 {
+        self.gems
+    }
+
+    fn gems_mut(&mut self) -> &mut i64 {
+        &mut self.gems
+    }
+}
+
+impl Prestige for RecipientRow {
+    fn prestige(&self) -> i64 {
+        self.prestige
+    }
+}
+
+impl MaxBet for RecipientRow {
+    fn level(&self) -> i32 {
+        self.level.unwrap_or_default()
+    }
+}
+
 impl Commands {
     pub async fn gift<
         Db: Database,
-        GoalsHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         GiftHandler: GiftManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -138,7 +180,16 @@ impl Commands {
             return Err(Error::SelfGift);
         }
 
-        let mut user_row = GiftHandler::sender(pool, interaction.user.id)
+        let mut tx = pool.begin().await?;
+
+        // Rows are locked in ascending id order, so two players gifting each
+        // other at once can't deadlock. Otherwise the recipient's lock is taken
+        // by the increment below.
+        if recipient.id < interaction.user.id {
+            GiftHandler::recipient(&mut *tx, recipient.id).await?;
+        }
+
+        let mut user_row = GiftHandler::sender(&mut *tx, interaction.user.id)
             .await
             .unwrap()
             .unwrap_or_else(|| SenderRow::new(interaction.user.id));
@@ -151,18 +202,35 @@ impl Commands {
 
         let amount = GIFT_AMOUNT * (user_row.prestige + 1);
 
-        GiftHandler::add_coins(pool, recipient.id, amount)
+        GiftHandler::add_coins(&mut *tx, recipient.id, amount)
             .await
             .unwrap();
+        LedgerHandler::coins(&mut *tx, recipient.id, amount, LedgerSource::Gift).await?;
 
-        Dispatch::<Db, GoalsHandler>::new(pool)
+        let mut recipient_row = GiftHandler::recipient(&mut *tx, recipient.id)
+            .await
+            .unwrap()
+            .unwrap_or_else(|| RecipientRow::new(recipient.id));
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
             .fire(
                 &mut user_row,
                 Event::Send(SendEvent::new(amount, interaction.user.id)),
             )
             .await?;
 
-        GiftHandler::save_sender(pool, user_row).await.unwrap();
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut recipient_row,
+                Event::Receive(ReceiveEvent::new(amount, interaction.user.id, recipient.id)),
+            )
+            .await?;
+
+        GiftHandler::save_sender(&mut *tx, user_row).await.unwrap();
+        GiftHandler::save_recipient(&mut *tx, recipient_row)
+            .await
+            .unwrap();
+        tx.commit().await?;
 
         let embed = CreateEmbed::new()
             .description(format!(
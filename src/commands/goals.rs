@@ -1,34 +1,83 @@
 use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
 use serenity::all::{
-    CommandInteraction, Context, CreateCommand, CreateEmbed, EditInteractionResponse, UserId,
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateEmbedFooter, EditInteractionResponse, ResolvedOption, ResolvedValue, UserId,
 };
+use sqlx::any::AnyQueryResult;
 use sqlx::{Database, FromRow, Pool};
+use zayden_core::{FormatNum, parse_options};
 
-use crate::{COIN, Coins, GamblingGoalsRow, Gems, GoalHandler, MaxBet, Prestige, Result, tomorrow};
+use crate::{
+    COIN, Coins, Error, GamblingConfig, GamblingGoalsRow, Gems, GoalHandler, GoalPeriod,
+    LedgerManager, LedgerSource, MaxBet, Prestige, Result, ShopCurrency, tomorrow,
+};
 
 use super::Commands;
 
 #[async_trait]
 pub trait GoalsManager<Db: Database> {
-    async fn row(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<GoalsRow>>;
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<GoalsRow>>;
+
+    /// Takes `cost` gems and sets `goal_reroll` to `date`, leaving the other
+    /// columns untouched.
+    async fn reroll(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+        cost: i64,
+        date: NaiveDate,
+    ) -> sqlx::Result<AnyQueryResult>;
 
     async fn full_rows(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         id: impl Into<UserId> + Send,
     ) -> sqlx::Result<Vec<GamblingGoalsRow>>;
 
+    /// Saves the progress of the goals, keyed by user, period and goal id.
     async fn update(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
+        rows: &[GamblingGoalsRow],
+    ) -> sqlx::Result<Vec<GamblingGoalsRow>>;
+
+    /// Replaces the user's goals of `period` with `rows`.
+    async fn reset(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+        period: GoalPeriod,
         rows: &[GamblingGoalsRow],
     ) -> sqlx::Result<Vec<GamblingGoalsRow>>;
 }
 
-#[derive(FromRow, Default)]
+#[derive(FromRow)]
 pub struct GoalsRow {
+    pub id: i64,
     pub coins: i64,
     pub gems: i64,
     pub level: Option<i32>,
     pub prestige: Option<i64>,
+    pub goal_reroll: Option<NaiveDate>,
+}
+
+impl GoalsRow {
+    pub fn new(id: impl Into<UserId>) -> Self {
+        let id = id.into();
+
+        Self {
+            id: id.get() as i64,
+            coins: 0,
+            gems: 0,
+            level: Some(0),
+            prestige: Some(0),
+            goal_reroll: None,
+        }
+    }
+
+    fn can_reroll(&self, today: NaiveDate) -> bool {
+        self.goal_reroll != Some(today)
+    }
 }
 
 impl Coins for GoalsRow {
@@ -64,42 +113,131 @@ impl MaxBet for GoalsRow {
 }
 
 impl Commands {
-    pub async fn goals<Db: Database, Manager: GoalsManager<Db>>(
+    pub async fn goals<Db: Database, Manager: GoalsManager<Db>, Ledger: LedgerManager<Db>>(
         ctx: &Context,
         interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
         pool: &Pool<Db>,
     ) -> Result<()> {
-        interaction.defer(ctx).await.unwrap();
+        interaction.defer(ctx).await?;
 
-        let row = Manager::row(pool, interaction.user.id)
-            .await
-            .unwrap()
-            .unwrap_or_default();
+        let cost = GamblingConfig::get(ctx).await.goal_reroll_cost;
 
-        let mut desc =
-            GoalHandler::get_user_progress::<Db, Manager>(pool, interaction.user.id, &row)
-                .await
-                .unwrap()
-                .into_iter()
-                .map(|goal| format!("{}\n\n", goal.description()))
-                .collect::<String>();
+        let mut tx = pool.begin().await?;
+
+        let mut row = Manager::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GoalsRow::new(interaction.user.id));
 
-        desc.push_str(&format!(
-            "Reward for completing __**each goals**__: 5,000 <:coin:{COIN}>\nReward for completing __**all goals**__: 1 💎\n\nGoals reset <t:{}:R>",
-            tomorrow(None)
-        ));
+        let mut goals =
+            GoalHandler::get_user_progress::<Db, Manager>(&mut *tx, interaction.user.id, &row)
+                .await?;
 
-        let embed = CreateEmbed::new().title("Daily Goals 📋").description(desc);
+        let now = Utc::now();
+        let today = now.naive_utc().date();
+
+        let mut options = parse_options(options);
+
+        if let Some(ResolvedValue::Integer(goal)) = options.remove("reroll") {
+            if !row.can_reroll(today) {
+                return Err(Error::RerollUsed(tomorrow(Some(now))));
+            }
+
+            if row.gems() < cost {
+                return Err(Error::InsufficientFunds {
+                    required: cost,
+                    currency: ShopCurrency::Gems,
+                });
+            }
+
+            let (daily, others): (Vec<_>, Vec<_>) = goals
+                .into_iter()
+                .partition(|goal| goal.period() == GoalPeriod::Daily);
+
+            let index = (goal - 1) as usize;
+
+            if daily.get(index).is_some_and(GamblingGoalsRow::is_complete) {
+                return Err(Error::GoalCompleted);
+            }
+
+            // Charged first, nothing is kept if no goal is left to reroll into
+            Manager::reroll(&mut *tx, interaction.user.id, cost, today).await?;
+            Ledger::gems(&mut *tx, interaction.user.id, -cost, LedgerSource::Reroll).await?;
+
+            let Some(daily) = GoalHandler::reroll::<Db, Manager>(
+                &mut *tx,
+                interaction.user.id,
+                &row,
+                daily,
+                index,
+            )
+            .await?
+            else {
+                return Err(Error::NoRerollAvailable);
+            };
+
+            row.goal_reroll = Some(today);
+            goals = daily.into_iter().chain(others).collect();
+        }
+
+        tx.commit().await?;
+
+        let desc = GoalPeriod::ALL
+            .into_iter()
+            .map(|period| {
+                let goals = goals
+                    .iter()
+                    .filter(|goal| goal.period() == period)
+                    .enumerate()
+                    .map(|(i, goal)| match period {
+                        GoalPeriod::Daily => format!("`{}` {}\n\n", i + 1, goal.description()),
+                        _ => format!("{}\n\n", goal.description()),
+                    })
+                    .collect::<String>();
+
+                format!(
+                    "## {}\n{goals}Reward for completing __**each goal**__: {} <:coin:{COIN}>\nReward for completing __**all goals**__: {} 💎\nGoals reset <t:{}:R>",
+                    period.title(),
+                    period.coin_reward().format(),
+                    period.gem_reward(),
+                    period.next_reset()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let footer = if !row.can_reroll(today) {
+            String::from("Daily reroll used, available again tomorrow")
+        } else if cost > 0 {
+            format!("Daily reroll available: {cost} 💎")
+        } else {
+            String::from("Daily reroll available: free")
+        };
+
+        let embed = CreateEmbed::new()
+            .title("Goals 📋")
+            .description(desc)
+            .footer(CreateEmbedFooter::new(footer));
 
         interaction
             .edit_response(ctx, EditInteractionResponse::new().embed(embed))
-            .await
-            .unwrap();
+            .await?;
 
         Ok(())
     }
 
     pub fn register_goals() -> CreateCommand {
-        CreateCommand::new("goals").description("Show your daily goal progress")
+        CreateCommand::new("goals")
+            .description("Show your daily, weekly and monthly goal progress")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "reroll",
+                    "Replace one of your daily goals, once a day",
+                )
+                .add_int_choice("Goal 1", 1)
+                .add_int_choice("Goal 2", 2)
+                .add_int_choice("Goal 3", 3),
+            )
     }
 }
@@ -6,22 +6,24 @@ use futures::StreamExt;
 use rand::rng;
 use rand::seq::SliceRandom;
 use serenity::all::{
-    Colour, CommandInteraction, ComponentInteraction, Context, CreateButton, CreateCommand,
-    CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
-    EditInteractionResponse, EmojiId, parse_emoji,
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, Context, CreateActionRow,
+    CreateButton, CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse, EmojiId, Message, ResolvedOption,
+    ResolvedValue,
 };
 use sqlx::{Database, Pool};
-use zayden_core::FormatNum;
+use zayden_core::{FormatNum, parse_options};
 
-use crate::events::{Dispatch, Event, GameEvent};
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::utils::signed;
 use crate::{
-    CARD_DECK, Coins, Error, GameCache, GameManager, GameRow, Gems, GoalsManager, Result,
-    ShopCurrency,
+    ActiveGames, CARD_DECK, COIN, Coins, GameCache, GameManager, GameRow, Gems, LedgerManager,
+    LedgerSource, Result, SessionManager, SessionRow, SessionStore, VerifyBet,
 };
 
 use super::Commands;
 
-const BUYIN: i64 = 1000;
+const HOUSE_EDGE: f64 = 0.03;
 
 static CARD_TO_NUM: LazyLock<HashMap<EmojiId, u8>> = LazyLock::new(|| {
     CARD_DECK
@@ -34,54 +36,61 @@ static CARD_TO_NUM: LazyLock<HashMap<EmojiId, u8>> = LazyLock::new(|| {
 impl Commands {
     pub async fn higher_lower<
         Db: Database,
-        GoalsHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
         pool: &Pool<Db>,
     ) -> Result<()> {
         interaction.defer(ctx).await.unwrap();
 
-        let mut row = GameHandler::row(pool, interaction.user.id)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| GameRow::new(interaction.user.id));
-
-        if row.coins() < BUYIN {
-            return Err(Error::InsufficientFunds {
-                required: BUYIN - row.coins(),
-                currency: ShopCurrency::Coins,
-            });
-        }
-
-        GameCache::can_play(ctx, interaction.user.id).await?;
+        let mut options = parse_options(options);
 
-        *row.coins_mut() -= BUYIN;
-
-        GameHandler::save(pool, row).await.unwrap();
+        let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
+            unreachable!("bet is required")
+        };
 
-        let mut deck = CARD_DECK.to_vec();
-        deck.shuffle(&mut rng());
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
 
-        let emoji = deck.pop().unwrap();
-        let num = CARD_TO_NUM.get(&emoji).unwrap();
+        GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "higherorlower").await?;
+        row.verify_bet(bet)?;
+        row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("higherorlower"),
+        )
+        .await?;
 
-        let embed = create_embed(&format!("<:{num}:{emoji}>"), 0, true);
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
 
-        let higher_btn = CreateButton::new("hol_higher").emoji('☝').label("Higher");
-        let lower_btn = CreateButton::new("hol_lower").emoji('👇').label("Lower");
+        let game = HigherLowerSession::new(bet);
 
         let msg = interaction
             .edit_response(
                 ctx,
                 EditInteractionResponse::new()
-                    .embed(embed)
-                    .button(higher_btn)
-                    .button(lower_btn),
+                    .embed(game.embed())
+                    .components(game.components()),
             )
-            .await
-            .unwrap();
+            .await?;
+
+        SessionHandler::save(
+            &mut *pool.acquire().await?,
+            SessionRow::new("higherorlower", &msg).stake(interaction.user.id, bet),
+        )
+        .await?;
+        SessionStore::insert(ctx, msg.id, game).await;
 
         let mut stream = msg
             .await_component_interactions(ctx)
@@ -89,113 +98,121 @@ impl Commands {
             .timeout(Duration::from_secs(120))
             .stream();
 
-        let mut payout = 0;
-        let mut prev_seq = String::new();
-
-        while let Some(interaction) = stream.next().await {
-            let mut desc_iter = interaction
-                .message
-                .embeds
-                .first()
-                .unwrap()
-                .description
-                .as_deref()
-                .unwrap()
-                .split("\n\n");
-
-            prev_seq = desc_iter.next().unwrap().to_string();
-            let prev_emoji = parse_emoji(prev_seq.split(' ').next_back().unwrap()).unwrap();
-            let prev_num = prev_emoji.name.parse::<u8>().unwrap();
-
-            let emoji = match deck.pop() {
-                Some(emoji) => emoji,
-                None => break,
-            };
-            let num = *CARD_TO_NUM.get(&emoji).unwrap();
-
-            payout = desc_iter
-                .next()
-                .unwrap()
-                .strip_prefix("Current Payout: ")
-                .unwrap()
-                .replace(',', "")
-                .parse::<i64>()
-                .unwrap();
-
-            let choice = interaction.data.custom_id.as_str();
-
-            let winner = if choice == "hol_higher" {
-                higher(
-                    ctx,
-                    &interaction,
-                    &mut prev_seq,
-                    prev_num,
-                    num,
-                    emoji,
-                    payout,
-                )
-                .await?
-            } else {
-                lower(
-                    ctx,
-                    &interaction,
-                    &mut prev_seq,
-                    prev_num,
-                    num,
-                    emoji,
-                    payout,
-                )
-                .await?
-            };
-
-            if !winner {
-                break;
+        let played: Result<()> = async {
+            while let Some(component) = stream.next().await {
+                let Some((finished, response)) =
+                    SessionStore::update(ctx, msg.id, |game: &mut HigherLowerSession| {
+                        match component.data.custom_id.as_str() {
+                            "hol_higher" => game.guess(true),
+                            "hol_lower" => game.guess(false),
+                            _ => game.cashed_out = true,
+                        }
+
+                        let response = CreateInteractionResponseMessage::new()
+                            .embed(game.embed())
+                            .components(game.components());
+
+                        (game.is_finished(), response)
+                    })
+                    .await
+                else {
+                    break;
+                };
+
+                if finished {
+                    component
+                        .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                        .await?;
+
+                    break;
+                }
+
+                component
+                    .create_response(ctx, CreateInteractionResponse::UpdateMessage(response))
+                    .await?;
             }
+
+            Ok(())
         }
+        .await;
 
-        let mut row = GameHandler::row(pool, interaction.user.id)
-            .await
-            .unwrap()
-            .unwrap();
+        // Removed however the rounds ended. On an error the session row is kept
+        // for `SessionStore::recover` to refund the bet.
+        let game = SessionStore::remove::<HigherLowerSession>(ctx, msg.id).await;
+        played?;
+
+        let Some(game) = game else {
+            return refund::<Db, GameHandler, LedgerHandler, SessionHandler>(
+                ctx,
+                interaction,
+                pool,
+                &msg,
+                bet,
+            )
+            .await;
+        };
+
+        // Timing out banks the current payout
+        let payout = if game.lost { 0 } else { game.payout };
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
 
         row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("higherorlower"),
+        )
+        .await?;
 
-        // 51 as the user starts at -1000
-        if payout == 51 * BUYIN {
+        if game.deck.is_empty() && !game.lost {
             row.add_gems(1);
+            LedgerHandler::gems(
+                &mut *tx,
+                interaction.user.id,
+                1,
+                LedgerSource::Game("higherorlower"),
+            )
+            .await?;
         }
 
-        let colour = if payout > 0 {
-            Colour::DARK_GREEN
-        } else {
-            Colour::RED
-        };
-
-        let coins = row.coins_str();
-
-        Dispatch::<Db, GoalsHandler>::new(pool)
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
             .fire(
                 &mut row,
-                Event::Game(GameEvent::new(
-                    "higherorlower",
-                    interaction.user.id,
-                    payout + BUYIN,
-                    payout > 0,
-                )),
+                Event::Game(
+                    GameEvent::new("higherorlower", interaction.user.id, bet, payout > bet)
+                        .set_streak(game.streak)
+                        .set_payout(payout),
+                ),
             )
             .await?;
 
-        GameHandler::save(pool, row).await.unwrap();
+        let coins = row.coins();
+
+        SessionHandler::delete(&mut *tx, msg.id).await?;
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
         GameCache::update(ctx, interaction.user.id).await;
 
-        let result = format!("Payout: {}", payout.format());
+        let colour = if payout > bet {
+            Colour::DARK_GREEN
+        } else {
+            Colour::RED
+        };
 
         let embed = CreateEmbed::new()
             .title("Higher or Lower")
             .description(format!(
-                "{}\n\nFinal Payout: {}\n\nThis game has ended.\n\n{result}\nYour coins: {coins}",
-                prev_seq,
-                payout.format()
+                "{}\n\nStreak: {}\n\nThis game has ended.\n\nPayout: {} ({})\nYour coins: {}",
+                game.seq,
+                game.streak,
+                payout.format(),
+                signed(payout - bet),
+                coins.format()
             ))
             .colour(colour);
 
@@ -213,99 +230,191 @@ impl Commands {
     }
 
     pub fn register_higher_lower() -> CreateCommand {
-        CreateCommand::new("higherorlower").description("Play a game of higher or lower")
+        CreateCommand::new("higherorlower")
+            .description("Play a game of higher or lower")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
+                    .required(true),
+            )
     }
 }
 
-fn create_embed(seq: &str, payout: i64, winner: bool) -> CreateEmbed {
-    let payout = payout.format();
+/// Returns the bet when the game state was lost from the session store.
+async fn refund<
+    Db: Database,
+    GameHandler: GameManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    pool: &Pool<Db>,
+    msg: &Message,
+    bet: i64,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+        .await?
+        .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+    row.add_coins(bet);
+    LedgerHandler::coins(
+        &mut *tx,
+        interaction.user.id,
+        bet,
+        LedgerSource::Refund("higherorlower"),
+    )
+    .await?;
+
+    SessionHandler::delete(&mut *tx, msg.id).await?;
+    GameHandler::save(&mut *tx, row).await?;
+    tx.commit().await?;
+
+    let embed = CreateEmbed::new()
+        .title("Higher or Lower")
+        .description(format!(
+            "This game was interrupted. Your bet of {} <:coin:{COIN}> was refunded.",
+            bet.format()
+        ))
+        .colour(Colour::ORANGE);
 
-    let desc = if winner {
-        format!("{seq}\n\nCurrent Payout: {payout}\n\nGuess the next number!")
-    } else {
-        format!("{seq}\n\nFinal Payout: {payout}")
-    };
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(Vec::new()),
+        )
+        .await?;
 
-    CreateEmbed::new()
-        .title("Higher or Lower")
-        .description(desc)
-        .colour(Colour::TEAL)
+    Ok(())
 }
 
-async fn higher(
-    ctx: &Context,
-    interaction: &ComponentInteraction,
-    seq: &mut String,
-    prev: u8,
-    next: u8,
-    emoji: EmojiId,
-    mut payout: i64,
-) -> Result<bool> {
-    seq.push(' ');
-
-    let winner = next >= prev;
-
-    if winner {
-        seq.push('☝');
-        payout += 1000
-    } else {
-        seq.push('❌');
-    }
+fn card_str(card: EmojiId) -> String {
+    format!("<:{}:{card}>", CARD_TO_NUM.get(&card).unwrap())
+}
 
-    seq.push_str(&format!(" <:{next}:{emoji}>"));
+pub struct HigherLowerSession {
+    deck: Vec<EmojiId>,
+    current: EmojiId,
+    seq: String,
+    streak: i64,
+    payout: i64,
+    lost: bool,
+    cashed_out: bool,
+}
 
-    let embed = create_embed(seq, payout, winner);
+impl HigherLowerSession {
+    fn new(bet: i64) -> Self {
+        let mut deck = CARD_DECK.to_vec();
+        deck.shuffle(&mut rng());
 
-    let msg = if winner {
-        CreateInteractionResponseMessage::new().embed(embed)
-    } else {
-        CreateInteractionResponseMessage::new()
-            .embed(embed)
-            .components(Vec::new())
-    };
+        let current = deck.pop().unwrap();
 
-    interaction
-        .create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
-        .await?;
+        Self {
+            deck,
+            current,
+            seq: card_str(current),
+            streak: 0,
+            payout: bet,
+            lost: false,
+            cashed_out: false,
+        }
+    }
 
-    Ok(winner)
-}
+    fn is_finished(&self) -> bool {
+        self.lost || self.cashed_out || self.deck.is_empty()
+    }
 
-async fn lower(
-    ctx: &Context,
-    interaction: &ComponentInteraction,
-    seq: &mut String,
-    prev: u8,
-    next: u8,
-    emoji: EmojiId,
-    mut payout: i64,
-) -> Result<bool> {
-    seq.push(' ');
-
-    let winner = next <= prev;
-
-    if winner {
-        seq.push('👇');
-        payout += 1000
-    } else {
-        seq.push('❌');
+    /// The multiplier is the inverse of the chance that the next card in the
+    /// remaining deck is strictly higher (or lower), less the house edge.
+    /// Never below 1x, so a correct guess can't lower the payout. Returns
+    /// `None` if the guess can't win.
+    fn multiplier(&self, higher: bool) -> Option<f64> {
+        let current = CARD_TO_NUM[&self.current];
+
+        let wins = self
+            .deck
+            .iter()
+            .map(|card| CARD_TO_NUM[card])
+            .filter(|num| {
+                if higher {
+                    *num > current
+                } else {
+                    *num < current
+                }
+            })
+            .count();
+
+        if wins == 0 {
+            return None;
+        }
+
+        Some((self.deck.len() as f64 / wins as f64 * (1.0 - HOUSE_EDGE)).max(1.0))
     }
 
-    seq.push_str(&format!(" <:{next}:{emoji}>"));
+    fn guess(&mut self, higher: bool) {
+        let multiplier = self.multiplier(higher);
+        let current = CARD_TO_NUM[&self.current];
 
-    let embed = create_embed(seq, payout, winner);
+        let next = self.deck.pop().unwrap();
+        let num = CARD_TO_NUM[&next];
 
-    let msg = if winner {
-        CreateInteractionResponseMessage::new().embed(embed)
-    } else {
-        CreateInteractionResponseMessage::new()
-            .embed(embed)
-            .components(Vec::new())
-    };
+        // Ties lose
+        let winner = if higher { num > current } else { num < current };
 
-    interaction
-        .create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
-        .await?;
+        self.seq.push(' ');
+
+        match multiplier {
+            Some(multiplier) if winner => {
+                self.seq.push(if higher { '☝' } else { '👇' });
+                self.payout = (self.payout as f64 * multiplier) as i64;
+                self.streak += 1;
+            }
+            _ => {
+                self.seq.push('❌');
+                self.lost = true;
+            }
+        }
+
+        self.seq.push_str(&format!(" {}", card_str(next)));
+        self.current = next;
+    }
 
-    Ok(winner)
+    fn embed(&self) -> CreateEmbed {
+        CreateEmbed::new()
+            .title("Higher or Lower")
+            .description(format!(
+                "{}\n\nStreak: {}\nCurrent Payout: {} <:coin:{COIN}>\n\nGuess the next card or cash out!",
+                self.seq,
+                self.streak,
+                self.payout.format()
+            ))
+            .colour(Colour::TEAL)
+    }
+
+    fn components(&self) -> Vec<CreateActionRow> {
+        let guess_btn = |id: &str, label: &str, emoji: char, higher: bool| {
+            let button = CreateButton::new(id)
+                .emoji(emoji)
+                .style(ButtonStyle::Secondary);
+
+            match self.multiplier(higher) {
+                Some(multiplier) => button.label(format!("{label} ({multiplier:.2}x)")),
+                None => button.label(label).disabled(true),
+            }
+        };
+
+        let cash_out = CreateButton::new("hol_cashout")
+            .label("Cash Out")
+            .emoji('💰')
+            .style(ButtonStyle::Success)
+            .disabled(self.streak == 0);
+
+        vec![CreateActionRow::Buttons(vec![
+            guess_btn("hol_higher", "Higher", '☝', true),
+            guess_btn("hol_lower", "Lower", '👇', false),
+            cash_out,
+        ])]
+    }
 }
@@ -0,0 +1,268 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use futures::StreamExt;
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateButton,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse, MessageId, ResolvedOption,
+    ResolvedValue, UserId,
+};
+use sqlx::any::AnyQueryResult;
+use sqlx::types::Json;
+use sqlx::{Database, FromRow, Pool};
+use zayden_core::FormatNum;
+
+use crate::events::{Event, EventRow, GameEvent, Subscriber};
+use crate::utils::signed;
+use crate::{COIN, Result, SHOP_ITEMS, SessionManager, SessionRow, SessionStore};
+
+use super::Commands;
+
+#[async_trait]
+pub trait HistoryManager<Db: Database> {
+    async fn save(conn: &mut Db::Connection, row: HistoryRow) -> sqlx::Result<AnyQueryResult>;
+
+    /// Most recent games first, 10 per page.
+    async fn rows(
+        pool: &Pool<Db>,
+        id: impl Into<UserId> + Send,
+        page_num: i64,
+    ) -> sqlx::Result<Vec<HistoryRow>>;
+}
+
+#[derive(FromRow)]
+pub struct HistoryRow {
+    pub user_id: i64,
+    pub game_id: String,
+    pub bet: i64,
+    pub payout: i64,
+    pub win: bool,
+    pub draw: bool,
+    pub effects: Json<Vec<String>>,
+    pub created_at: NaiveDateTime,
+}
+
+impl HistoryRow {
+    fn outcome(&self) -> &'static str {
+        if self.draw {
+            "Draw"
+        } else if self.win {
+            "Won"
+        } else {
+            "Lost"
+        }
+    }
+
+    pub fn as_desc(&self) -> String {
+        let mut desc = format!(
+            "**{}** - {} <t:{}:R>\nBet: {} <:coin:{COIN}> | Payout: {} ({})",
+            self.game_id,
+            self.outcome(),
+            self.created_at.and_utc().timestamp(),
+            self.bet.format(),
+            self.payout.format(),
+            signed(self.payout - self.bet)
+        );
+
+        if !self.effects.is_empty() {
+            let effects = self
+                .effects
+                .iter()
+                .map(|id| match SHOP_ITEMS.get(id) {
+                    Some(item) => format!("{} {}", item.emoji(), item.name),
+                    None => id.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            desc.push_str(&format!("\nEffects: {effects}"));
+        }
+
+        desc
+    }
+}
+
+impl From<&GameEvent> for HistoryRow {
+    fn from(event: &GameEvent) -> Self {
+        Self {
+            user_id: event.user_id.get() as i64,
+            game_id: event.game_id.clone(),
+            bet: event.bet,
+            payout: event.payout,
+            win: event.win,
+            draw: event.draw,
+            effects: Json(event.effects.clone()),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+/// Records every game played.
+pub struct HistorySubscriber<Manager>(PhantomData<Manager>);
+
+#[async_trait]
+impl<Db: Database, Manager: HistoryManager<Db>> Subscriber<Db> for HistorySubscriber<Manager> {
+    async fn on_event(
+        conn: &mut Db::Connection,
+        _row: &mut dyn EventRow,
+        event: &Event,
+    ) -> sqlx::Result<()> {
+        if let Event::Game(game) = event {
+            Manager::save(conn, HistoryRow::from(game)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct HistorySession {
+    user_id: UserId,
+    title: String,
+    page: i64,
+}
+
+impl Commands {
+    pub async fn history<
+        Db: Database,
+        Manager: HistoryManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        mut options: Vec<ResolvedOption<'_>>,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        interaction.defer(ctx).await.unwrap();
+
+        let user = match options.pop() {
+            Some(option) => {
+                let ResolvedValue::User(user, _) = option.value else {
+                    unreachable!("value must be a user")
+                };
+                user
+            }
+            None => &interaction.user,
+        };
+
+        let title = format!("📜 Game History ({})", user.display_name());
+
+        let rows = Manager::rows(pool, user.id, 1).await?;
+
+        let msg = interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(history_embed(&title, rows, 1))
+                    .button(CreateButton::new("history_previous").label("<"))
+                    .button(CreateButton::new("history_next").label(">")),
+            )
+            .await
+            .unwrap();
+
+        let session = HistorySession {
+            user_id: user.id,
+            title,
+            page: 1,
+        };
+
+        SessionHandler::save(
+            &mut *pool.acquire().await?,
+            SessionRow::new("history", &msg),
+        )
+        .await?;
+        SessionStore::insert(ctx, msg.id, session).await;
+
+        let mut stream = msg
+            .await_component_interactions(ctx)
+            .timeout(Duration::from_secs(120))
+            .stream();
+
+        while let Some(component) = stream.next().await {
+            run_component::<Db, Manager>(ctx, pool, msg.id, component).await?;
+        }
+
+        SessionStore::remove::<HistorySession>(ctx, msg.id).await;
+        SessionHandler::delete(&mut *pool.acquire().await?, msg.id).await?;
+
+        interaction
+            .edit_response(ctx, EditInteractionResponse::new().components(Vec::new()))
+            .await?;
+
+        Ok(())
+    }
+
+    pub fn register_history() -> CreateCommand {
+        CreateCommand::new("history")
+            .description("Show your most recent games")
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "user",
+                "The user's history to show",
+            ))
+    }
+}
+
+async fn run_component<Db: Database, Manager: HistoryManager<Db>>(
+    ctx: &Context,
+    pool: &Pool<Db>,
+    msg_id: MessageId,
+    interaction: ComponentInteraction,
+) -> Result<()> {
+    let custom_id = interaction.data.custom_id.strip_prefix("history_").unwrap();
+
+    let (user_id, title, page) =
+        SessionStore::update(ctx, msg_id, |session: &mut HistorySession| {
+            (session.user_id, session.title.clone(), session.page)
+        })
+        .await
+        .expect("session is removed once the collector ends");
+
+    let page_number = match custom_id {
+        "previous" => (page - 1).max(1),
+        "next" => page + 1,
+        _ => unreachable!("Invalid custom id"),
+    };
+
+    SessionStore::update(ctx, msg_id, |session: &mut HistorySession| {
+        session.page = page_number
+    })
+    .await;
+
+    let rows = Manager::rows(pool, user_id, page_number).await?;
+
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new().embed(history_embed(
+                    &title,
+                    rows,
+                    page_number,
+                )),
+            ),
+        )
+        .await
+        .unwrap();
+
+    Ok(())
+}
+
+fn history_embed(title: &str, rows: Vec<HistoryRow>, page_number: i64) -> CreateEmbed {
+    let desc = if rows.is_empty() {
+        String::from("No games played")
+    } else {
+        rows.iter()
+            .map(HistoryRow::as_desc)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    CreateEmbed::new()
+        .title(title)
+        .description(desc)
+        .footer(CreateEmbedFooter::new(format!("Page {page_number}")))
+        .colour(Colour::TEAL)
+}
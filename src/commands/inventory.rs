@@ -5,13 +5,16 @@ use serenity::all::{
     CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
     CreateEmbed, EditInteractionResponse, Mentionable, ResolvedOption, ResolvedValue, UserId,
 };
+use sqlx::any::AnyQueryResult;
 use sqlx::types::Json;
 use sqlx::{Database, Pool, prelude::FromRow};
 use zayden_core::parse_options;
 
+use crate::events::{Dispatch, Event, ItemUseEvent, Subscriber};
 use crate::shop::{SHOP_ITEMS, ShopCurrency, ShopItem, ShopPage};
 use crate::{
-    COIN, Coins, EffectsManager, Error, GEM, GamblingItem, Gems, ItemInventory, Mining, Result,
+    COIN, Coins, EffectsManager, Error, GEM, GamblingItem, Gems, ItemInventory, MaxBet, Mining,
+    Prestige, Result,
 };
 
 use super::Commands;
@@ -55,6 +58,15 @@ pub trait InventoryManager<Db: Database> {
         item_id: &str,
         amount: i64,
     ) -> sqlx::Result<i64>;
+
+    /// Must lock the row (`SELECT ... FOR UPDATE`), as `use_save` writes its
+    /// coins and gems back at the end of the transaction.
+    async fn use_row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<UseRow>>;
+
+    async fn use_save(conn: &mut Db::Connection, row: UseRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(Default, FromRow)]
@@ -185,9 +197,65 @@ impl Mining for InventoryRow {
     }
 }
 
+#[derive(FromRow)]
+pub struct UseRow {
+    pub id: i64,
+    pub coins: i64,
+    pub gems: i64,
+    pub level: Option<i32>,
+    pub prestige: i64,
+}
+
+impl UseRow {
+    fn new(id: impl Into<UserId>) -> Self {
+        let id = id.into();
+
+        Self {
+            id: id.get() as i64,
+            coins: 0,
+            gems: 0,
+            level: Some(0),
+            prestige: 0,
+        }
+    }
+}
+
+impl Coins for UseRow {
+    fn coins(&self) -> i64 {
+        self.coins
+    }
+
+    fn coins_mut(&mut self) -> &mut i64 {
+        &mut self.coins
+    }
+}
+
+impl Gems for UseRow {
+    fn gems(&self) -> i64 {
+        self.gems
+    }
+
+    fn gems_mut(&mut self) -> &mut i64 {
+        &mut self.gems
+    }
+}
+
+impl Prestige for UseRow {
+    fn prestige(&self) -> i64 {
+        self.prestige
+    }
+}
+
+impl MaxBet for UseRow {
+    fn level(&self) -> i32 {
+        self.level.unwrap_or_default()
+    }
+}
+
 impl Commands {
     pub async fn inventory<
         Db: Database,
+        EventHandler: Subscriber<Db>,
         EffectsHandler: EffectsManager<Db>,
         InventoryHandler: InventoryManager<Db>,
     >(
@@ -207,8 +275,13 @@ impl Commands {
                     unreachable!("Option must be a subcommand")
                 };
 
-                use_item::<Db, EffectsHandler, InventoryHandler>(ctx, interaction, options, pool)
-                    .await
+                use_item::<Db, EventHandler, EffectsHandler, InventoryHandler>(
+                    ctx,
+                    interaction,
+                    options,
+                    pool,
+                )
+                .await
             }
             _ => unreachable!("Invalid subcommand"),
         }
@@ -334,6 +407,7 @@ async fn show<Db: Database, Manager: InventoryManager<Db>>(
 
 async fn use_item<
     Db: Database,
+    EventHandler: Subscriber<Db>,
     EffectsHandler: EffectsManager<Db>,
     InventoryHandler: InventoryManager<Db>,
 >(
@@ -380,6 +454,18 @@ async fn use_item<
             .unwrap();
     }
 
+    let mut row = InventoryHandler::use_row(&mut *tx, interaction.user.id)
+        .await?
+        .unwrap_or_else(|| UseRow::new(interaction.user.id));
+
+    Dispatch::<Db, EventHandler>::new(&mut *tx)
+        .fire(
+            &mut row,
+            Event::ItemUse(ItemUseEvent::new(interaction.user.id, item.id, amount)),
+        )
+        .await?;
+
+    InventoryHandler::use_save(&mut *tx, row).await?;
     tx.commit().await.unwrap();
 
     let embed = CreateEmbed::new().description(format!(
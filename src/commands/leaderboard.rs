@@ -5,14 +5,14 @@ use futures::StreamExt;
 use serenity::all::{
     Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context, CreateButton,
     CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
-    CreateInteractionResponseMessage, EditInteractionResponse, Mentionable, Message,
+    CreateInteractionResponseMessage, EditInteractionResponse, Mentionable, MessageId,
     ResolvedOption, ResolvedValue, UserId,
 };
 use sqlx::{Database, Pool, prelude::FromRow};
 use zayden_core::{FormatNum, cache::GuildMembersCache};
 
 use crate::shop::{EGGPLANT, LOTTO_TICKET};
-use crate::{Coins, Gems, Result};
+use crate::{Coins, Gems, Result, SessionManager, SessionRow, SessionStore};
 
 use super::Commands;
 
@@ -120,8 +120,17 @@ pub struct LottoTicketRow {
     pub quantity: i64,
 }
 
+pub struct LeaderboardSession {
+    leaderboard: String,
+    page: i64,
+}
+
 impl Commands {
-    pub async fn leaderboard<Db: Database, Manager: LeaderboardManager<Db>>(
+    pub async fn leaderboard<
+        Db: Database,
+        Manager: LeaderboardManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
         ctx: &Context,
         interaction: &CommandInteraction,
         mut options: Vec<ResolvedOption<'_>>,
@@ -178,15 +187,30 @@ impl Commands {
             .await
             .unwrap();
 
+        let session = LeaderboardSession {
+            leaderboard: leaderboard.to_string(),
+            page: 1,
+        };
+
+        SessionHandler::save(
+            &mut *pool.acquire().await?,
+            SessionRow::new("leaderboard", &msg),
+        )
+        .await?;
+        SessionStore::insert(ctx, msg.id, session).await;
+
         let mut stream = msg
             .await_component_interactions(ctx)
             .timeout(Duration::from_secs(120))
             .stream();
 
         while let Some(component) = stream.next().await {
-            run_component::<Db, Manager>(ctx, pool, &users, &msg, component).await?;
+            run_component::<Db, Manager>(ctx, pool, &users, msg.id, component).await?;
         }
 
+        SessionStore::remove::<LeaderboardSession>(ctx, msg.id).await;
+        SessionHandler::delete(&mut *pool.acquire().await?, msg.id).await?;
+
         interaction
             .edit_response(ctx, EditInteractionResponse::new().components(Vec::new()))
             .await?;
@@ -217,7 +241,7 @@ async fn run_component<Db: Database, Manager: LeaderboardManager<Db>>(
     ctx: &Context,
     pool: &Pool<Db>,
     users: &[i64],
-    msg: &Message,
+    msg_id: MessageId,
     interaction: ComponentInteraction,
 ) -> Result<()> {
     let custom_id = interaction
@@ -226,43 +250,31 @@ async fn run_component<Db: Database, Manager: LeaderboardManager<Db>>(
         .strip_prefix("leaderboard_")
         .unwrap();
 
-    let embed = msg.embeds.first().unwrap();
-
-    let leaderboard = embed
-        .title
-        .as_ref()
-        .unwrap()
-        .strip_prefix("🏁 Leaderboard (")
-        .unwrap()
-        .strip_suffix(")")
-        .unwrap();
-
-    let mut page_number: i64 = embed
-        .footer
-        .as_ref()
-        .unwrap()
-        .text
-        .strip_prefix("Page ")
-        .unwrap()
-        .parse()
-        .unwrap();
+    let (leaderboard, page) =
+        SessionStore::update(ctx, msg_id, |session: &mut LeaderboardSession| {
+            (session.leaderboard.clone(), session.page)
+        })
+        .await
+        .expect("session is removed once the collector ends");
+    let leaderboard = leaderboard.as_str();
 
-    match custom_id {
-        "previous" => {
-            page_number = (page_number - 1).max(1);
-        }
+    let page_number = match custom_id {
+        "previous" => (page - 1).max(1),
         "user" => {
             let row_num = get_row_number::<Db, Manager>(leaderboard, pool, interaction.user.id)
                 .await
                 .unwrap();
-            page_number = row_num / 10 + 1;
-        }
-        "next" => {
-            page_number += 1;
+            row_num / 10 + 1
         }
+        "next" => page + 1,
         _ => unreachable!("Invalid custom id"),
     };
 
+    SessionStore::update(ctx, msg_id, |session: &mut LeaderboardSession| {
+        session.page = page_number
+    })
+    .await;
+
     let rows = get_rows::<Db, Manager>(leaderboard, pool, users, page_number).await;
 
     let desc = rows
@@ -272,9 +284,11 @@ async fn run_component<Db: Database, Manager: LeaderboardManager<Db>>(
         .collect::<Vec<_>>()
         .join("\n\n");
 
-    let embed = CreateEmbed::from(embed.clone())
+    let embed = CreateEmbed::new()
+        .title(format!("🏁 Leaderboard ({leaderboard})"))
+        .description(desc)
         .footer(CreateEmbedFooter::new(format!("Page {}", page_number)))
-        .description(desc);
+        .colour(Colour::TEAL);
 
     interaction
         .create_response(
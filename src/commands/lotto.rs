@@ -2,7 +2,7 @@ use serenity::all::{
     CommandInteraction, Context, CreateCommand, CreateEmbed, EditInteractionResponse,
 };
 use sqlx::{Database, Pool};
-use zayden_core::FormatNum;
+use zayden_core::{CronJob, FormatNum};
 
 use crate::shop::LOTTO_TICKET;
 use crate::{COIN, Commands, Lotto, LottoManager, LottoRow, Result, jackpot};
@@ -27,7 +27,7 @@ impl Commands {
         let lotto_emoji = LOTTO_TICKET.emoji();
 
         let timestamp = {
-            Lotto::cron_job::<Db, Manager>()
+            CronJob::<Db>::new(Lotto::SCHEDULE)
                 .schedule
                 .upcoming(chrono::Utc)
                 .next()
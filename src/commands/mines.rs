@@ -0,0 +1,342 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use rand::rng;
+use rand::seq::index::sample;
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    ResolvedOption, ResolvedValue,
+};
+use sqlx::{Database, Pool};
+use zayden_core::{FormatNum, parse_options};
+
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::utils::signed;
+use crate::{
+    ActiveGames, BLANK, COIN, Coins, EffectsManager, GameCache, GameManager, GameRow,
+    LedgerManager, LedgerSource, Result, SessionManager, SessionRow, VerifyBet,
+};
+
+use super::Commands;
+
+const SIZE: usize = 5;
+// The last slot of the grid holds the cash out button
+const TILES: usize = SIZE * SIZE - 1;
+const HOUSE_EDGE: f64 = 0.03;
+const DEFAULT_BOMBS: usize = 3;
+
+impl Commands {
+    pub async fn mines<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        EffectsHandler: EffectsManager<Db> + Send,
+        GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        interaction.defer(ctx).await.unwrap();
+
+        let mut options = parse_options(options);
+
+        let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
+            unreachable!("bet is required")
+        };
+
+        let bombs = match options.remove("bombs") {
+            Some(ResolvedValue::Integer(bombs)) => bombs as usize,
+            _ => DEFAULT_BOMBS,
+        };
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "mines").await?;
+        row.verify_bet(bet)?;
+        row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("mines"),
+        )
+        .await?;
+
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
+
+        let mut game = Mines::new(bet, bombs);
+
+        let msg = interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(game.embed())
+                    .components(game.components()),
+            )
+            .await?;
+
+        SessionHandler::save(
+            &mut *pool.acquire().await?,
+            SessionRow::new("mines", &msg).stake(interaction.user.id, bet),
+        )
+        .await?;
+
+        let mut stream = msg
+            .await_component_interactions(ctx)
+            .author_id(interaction.user.id)
+            .timeout(Duration::from_secs(120))
+            .stream();
+
+        while let Some(component) = stream.next().await {
+            run_component(ctx, &component, &mut game).await?;
+
+            if game.is_finished() {
+                break;
+            }
+        }
+
+        let payout = game.payout();
+        let winner = payout > bet;
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        let (payout, effects) =
+            EffectsHandler::payout(&mut *tx, interaction.user.id, bet, payout, winner).await;
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("mines"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Game(
+                    GameEvent::new("mines", interaction.user.id, bet, winner)
+                        .set_payout(payout)
+                        .set_effects(effects),
+                ),
+            )
+            .await?;
+
+        let coins = row.coins();
+
+        GameHandler::save(&mut *tx, row).await?;
+        SessionHandler::delete(&mut *tx, msg.id).await?;
+        tx.commit().await?;
+        GameCache::update(ctx, interaction.user.id).await;
+
+        interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(game.result_embed(payout, coins))
+                    .components(game.components()),
+            )
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    pub fn register_mines() -> CreateCommand {
+        CreateCommand::new("mines")
+            .description("Reveal safe tiles and cash out before you hit a bomb")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
+                    .required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "bombs",
+                    "The number of bombs to hide. Defaults to 3.",
+                )
+                .min_int_value(1)
+                .max_int_value(TILES as u64 - 1),
+            )
+    }
+}
+
+async fn run_component(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    game: &mut Mines,
+) -> Result<()> {
+    match component.data.custom_id.as_str() {
+        "mines_cashout" => game.cashed_out = true,
+        custom_id => {
+            let tile = custom_id
+                .strip_prefix("mines_")
+                .and_then(|tile| tile.parse::<usize>().ok())
+                .expect("Invalid custom id");
+
+            game.reveal(tile);
+        }
+    }
+
+    component
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .embed(game.embed())
+                    .components(game.components()),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+struct Mines {
+    bet: i64,
+    bombs: Vec<bool>,
+    revealed: Vec<bool>,
+    bomb_count: usize,
+    safe_revealed: usize,
+    hit_bomb: bool,
+    cashed_out: bool,
+}
+
+impl Mines {
+    fn new(bet: i64, bomb_count: usize) -> Self {
+        let mut bombs = vec![false; TILES];
+        sample(&mut rng(), TILES, bomb_count)
+            .into_iter()
+            .for_each(|i| bombs[i] = true);
+
+        Self {
+            bet,
+            bombs,
+            revealed: vec![false; TILES],
+            bomb_count,
+            safe_revealed: 0,
+            hit_bomb: false,
+            cashed_out: false,
+        }
+    }
+
+    fn reveal(&mut self, tile: usize) {
+        if self.revealed[tile] {
+            return;
+        }
+
+        self.revealed[tile] = true;
+
+        if self.bombs[tile] {
+            self.hit_bomb = true;
+        } else {
+            self.safe_revealed += 1;
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.hit_bomb || self.cashed_out || self.safe_revealed == TILES - self.bomb_count
+    }
+
+    // The chance of surviving k reveals is C(TILES - bombs, k) / C(TILES, k),
+    // so the fair multiplier is its inverse, reduced by the house edge.
+    fn multiplier(&self) -> f64 {
+        if self.safe_revealed == 0 {
+            return 1.0;
+        }
+
+        let fair = (0..self.safe_revealed)
+            .map(|i| (TILES - i) as f64 / (TILES - self.bomb_count - i) as f64)
+            .product::<f64>();
+
+        fair * (1.0 - HOUSE_EDGE)
+    }
+
+    fn payout(&self) -> i64 {
+        if self.hit_bomb {
+            0
+        } else {
+            (self.bet as f64 * self.multiplier()) as i64
+        }
+    }
+
+    fn embed(&self) -> CreateEmbed {
+        CreateEmbed::new()
+            .title("💣 Mines 💣")
+            .description(format!(
+                "Your bet: {} <:coin:{COIN}>\nBombs: {}\n\nMultiplier: **{:.2}x**\nCash out now for {} <:coin:{COIN}>",
+                self.bet.format(),
+                self.bomb_count,
+                self.multiplier(),
+                self.payout().format()
+            ))
+            .colour(Colour::TEAL)
+    }
+
+    fn result_embed(&self, payout: i64, coins: i64) -> CreateEmbed {
+        let (title, colour) = if self.hit_bomb {
+            ("💣 Mines 💣 - You Hit a Bomb!", Colour::RED)
+        } else if payout > self.bet {
+            ("💣 Mines 💣 - You Cashed Out!", Colour::DARK_GREEN)
+        } else {
+            ("💣 Mines 💣 - Push!", Colour::ORANGE)
+        };
+
+        CreateEmbed::new()
+            .title(title)
+            .description(format!(
+                "Your bet: {} <:coin:{COIN}>\nBombs: {}\nSafe tiles revealed: {}\n\nPayout: {} ({})\nYour coins: {}",
+                self.bet.format(),
+                self.bomb_count,
+                self.safe_revealed,
+                payout.format(),
+                signed(payout - self.bet),
+                coins.format()
+            ))
+            .colour(colour)
+    }
+
+    fn components(&self) -> Vec<CreateActionRow> {
+        let finished = self.is_finished();
+
+        let mut buttons = (0..TILES)
+            .map(|i| {
+                let button = CreateButton::new(format!("mines_{i}"));
+
+                match (self.revealed[i], self.bombs[i]) {
+                    (true, true) => button.emoji('💥').style(ButtonStyle::Danger),
+                    (true, false) => button.emoji('💎').style(ButtonStyle::Success),
+                    (false, true) if finished => button.emoji('💣').style(ButtonStyle::Secondary),
+                    _ => button.emoji(BLANK).style(ButtonStyle::Secondary),
+                }
+                .disabled(finished || self.revealed[i])
+            })
+            .collect::<Vec<_>>();
+
+        buttons.push(
+            CreateButton::new("mines_cashout")
+                .emoji('💰')
+                .style(ButtonStyle::Primary)
+                .disabled(finished || self.safe_revealed == 0),
+        );
+
+        buttons
+            .chunks(SIZE)
+            .map(|row| CreateActionRow::Buttons(row.to_vec()))
+            .collect()
+    }
+}
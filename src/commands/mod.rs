@@ -1,20 +1,27 @@
+pub mod blackjack;
 pub mod coinflip;
 pub mod craft;
+pub mod crash;
 pub mod daily;
 pub mod dig;
 pub mod gift;
 pub mod goals;
 pub mod higher_lower;
+pub mod history;
 pub mod inventory;
 pub mod leaderboard;
 pub mod lotto;
 pub mod mine;
+pub mod mines;
+pub mod poker;
 pub mod prestige;
 pub mod profile;
 pub mod roll;
+pub mod roulette;
 pub mod rps;
 pub mod send;
 pub mod shop;
+pub mod slots;
 pub mod tictactoe;
 pub mod work;
 
@@ -0,0 +1,585 @@
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, Context, CreateActionRow,
+    CreateButton, CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse, Mentionable, Message,
+    ResolvedOption, ResolvedValue, UserId,
+};
+use sqlx::{Database, Pool};
+use zayden_core::{FormatNum, parse_options};
+
+use crate::duel::ephemeral;
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::poker::{Action, Table};
+use crate::utils::signed;
+use crate::{
+    ActiveGames, COIN, Coins, Error, GameCache, GameLock, GameManager, GameRow, LedgerManager,
+    LedgerSource, Result, SessionManager, SessionRow, VerifyBet,
+};
+
+use super::Commands;
+
+const MIN_BUY_IN: i64 = 100;
+const MAX_SEATS: usize = 8;
+const TURN_TIMEOUT: Duration = Duration::from_secs(60);
+
+impl Commands {
+    pub async fn poker<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        interaction.defer(ctx).await.unwrap();
+
+        let mut options = parse_options(options);
+
+        let Some(ResolvedValue::Integer(buy_in)) = options.remove("buyin") else {
+            unreachable!("buyin is required")
+        };
+
+        if buy_in < MIN_BUY_IN {
+            return Err(Error::MinimumBetAmount(MIN_BUY_IN));
+        }
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "poker").await?;
+        row.verify_bet(buy_in)?;
+        row.bet(buy_in);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -buy_in,
+            LedgerSource::Game("poker"),
+        )
+        .await?;
+
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
+
+        let mut players = vec![interaction.user.id];
+        // Seated players stay locked out of other games until the table closes
+        let mut locks = Vec::new();
+
+        let msg = interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(lobby_embed(interaction.user.id, buy_in, &players))
+                    .components(lobby_components()),
+            )
+            .await?;
+
+        SessionHandler::save(
+            &mut *pool.acquire().await?,
+            session_row(&msg, buy_in, players.iter().copied()),
+        )
+        .await?;
+
+        let started = run_lobby::<Db, GameHandler, LedgerHandler, SessionHandler>(
+            ctx,
+            interaction,
+            &msg,
+            pool,
+            buy_in,
+            &mut players,
+            &mut locks,
+        )
+        .await?;
+
+        if !started {
+            let mut tx = pool.begin().await?;
+
+            for id in players {
+                let mut row = GameHandler::row(&mut *tx, id)
+                    .await?
+                    .unwrap_or_else(|| GameRow::new(id));
+
+                row.add_coins(buy_in);
+                LedgerHandler::coins(&mut *tx, id, buy_in, LedgerSource::Game("poker")).await?;
+
+                GameHandler::save(&mut *tx, row).await?;
+            }
+
+            SessionHandler::delete(&mut *tx, msg.id).await?;
+            tx.commit().await?;
+
+            let embed = CreateEmbed::new()
+                .title("Texas Hold'em")
+                .description(
+                    "The table closed before the game started. All buy-ins have been refunded.",
+                )
+                .colour(Colour::TEAL);
+
+            interaction
+                .edit_response(
+                    ctx,
+                    EditInteractionResponse::new()
+                        .embed(embed)
+                        .components(Vec::new()),
+                )
+                .await?;
+
+            return Ok(());
+        }
+
+        let mut table = Table::new(buy_in, players);
+
+        interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(table_embed(&table))
+                    .components(table_components(&table)),
+            )
+            .await?;
+
+        play_hand(ctx, interaction, &msg, &mut table).await?;
+
+        let ranks = table.showdown();
+        table.settle();
+
+        let mut tx = pool.begin().await?;
+
+        for seat in table.seats.iter() {
+            let mut row = GameHandler::row(&mut *tx, seat.user_id)
+                .await?
+                .unwrap_or_else(|| GameRow::new(seat.user_id));
+
+            row.add_coins(seat.stack);
+            LedgerHandler::coins(
+                &mut *tx,
+                seat.user_id,
+                seat.stack,
+                LedgerSource::Game("poker"),
+            )
+            .await?;
+
+            Dispatch::<Db, EventHandler>::new(&mut *tx)
+                .fire(
+                    &mut row,
+                    Event::Game(
+                        GameEvent::new(
+                            "poker",
+                            seat.user_id,
+                            seat.contributed,
+                            seat.stack > buy_in,
+                        )
+                        // The chips left uncommitted are not part of the bet
+                        .set_payout(seat.stack - (buy_in - seat.contributed)),
+                    ),
+                )
+                .await?;
+
+            GameHandler::save(&mut *tx, row).await?;
+        }
+
+        SessionHandler::delete(&mut *tx, msg.id).await?;
+        tx.commit().await?;
+
+        for seat in table.seats.iter() {
+            GameCache::update(ctx, seat.user_id).await;
+        }
+
+        let results = table
+            .seats
+            .iter()
+            .zip(ranks)
+            .map(|(seat, rank)| {
+                let hand = match rank {
+                    Some(rank) => format!("{} {} ({})", seat.hole[0], seat.hole[1], rank.category),
+                    None if seat.folded => String::from("Folded"),
+                    None => String::from("Mucked"),
+                };
+
+                format!(
+                    "{} - {hand}\n{} <:coin:{COIN}> ({})",
+                    seat.user_id.mention(),
+                    seat.stack.format(),
+                    signed(seat.stack - buy_in)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let embed = CreateEmbed::new()
+            .title("Texas Hold'em - Showdown")
+            .description(format!("{}\n\n{results}", board(&table)))
+            .colour(Colour::DARK_GREEN);
+
+        interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(embed)
+                    .components(Vec::new()),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub fn register_poker() -> CreateCommand {
+        CreateCommand::new("poker")
+            .description("Open a Texas Hold'em table")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "buyin",
+                    "The chips every player brings to the table.",
+                )
+                .required(true),
+            )
+    }
+}
+
+async fn run_lobby<
+    Db: Database,
+    Manager: GameManager<Db>,
+    Ledger: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    msg: &Message,
+    pool: &Pool<Db>,
+    buy_in: i64,
+    players: &mut Vec<UserId>,
+    locks: &mut Vec<GameLock>,
+) -> Result<bool> {
+    let host = interaction.user.id;
+
+    let mut stream = msg
+        .await_component_interactions(ctx)
+        .timeout(Duration::from_secs(120))
+        .stream();
+
+    while let Some(component) = stream.next().await {
+        match component.data.custom_id.as_str() {
+            "poker_join" => {
+                if players.contains(&component.user.id) {
+                    ephemeral(ctx, &component, "You are already seated at this table.").await?;
+                    continue;
+                }
+
+                if players.len() >= MAX_SEATS {
+                    ephemeral(ctx, &component, "This table is full.").await?;
+                    continue;
+                }
+
+                let mut tx = pool.begin().await?;
+                let mut row = Manager::row(&mut *tx, component.user.id)
+                    .await?
+                    .unwrap_or_else(|| GameRow::new(component.user.id));
+
+                if let Err(e) = GameCache::can_play(ctx, component.user.id)
+                    .await
+                    .and_then(|_| row.verify_bet(buy_in))
+                {
+                    ephemeral(ctx, &component, &e.to_string()).await?;
+                    continue;
+                }
+
+                let lock = match ActiveGames::lock(ctx, component.user.id, "poker").await {
+                    Ok(lock) => lock,
+                    Err(e) => {
+                        ephemeral(ctx, &component, &e.to_string()).await?;
+                        continue;
+                    }
+                };
+
+                row.bet(buy_in);
+                Ledger::coins(
+                    &mut *tx,
+                    component.user.id,
+                    -buy_in,
+                    LedgerSource::Game("poker"),
+                )
+                .await?;
+
+                Manager::save(&mut *tx, row).await?;
+                SessionHandler::save(
+                    &mut *tx,
+                    session_row(
+                        msg,
+                        buy_in,
+                        players.iter().copied().chain([component.user.id]),
+                    ),
+                )
+                .await?;
+                tx.commit().await?;
+
+                players.push(component.user.id);
+                locks.push(lock);
+
+                component
+                    .create_response(
+                        ctx,
+                        CreateInteractionResponse::UpdateMessage(
+                            CreateInteractionResponseMessage::new()
+                                .embed(lobby_embed(host, buy_in, players)),
+                        ),
+                    )
+                    .await?;
+            }
+            "poker_start" if component.user.id == host => {
+                if players.len() < 2 {
+                    ephemeral(ctx, &component, "At least 2 players are needed to start.").await?;
+                    continue;
+                }
+
+                component
+                    .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                    .await?;
+
+                return Ok(true);
+            }
+            "poker_cancel" if component.user.id == host => {
+                component
+                    .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                    .await?;
+
+                return Ok(false);
+            }
+            _ => {
+                ephemeral(ctx, &component, "Only the host can do that.").await?;
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+async fn play_hand(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    msg: &Message,
+    table: &mut Table,
+) -> Result<()> {
+    while !table.is_finished() {
+        let turn = table.current_player();
+        let deadline = Instant::now() + TURN_TIMEOUT;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            let Some(component) = msg
+                .await_component_interaction(ctx)
+                .timeout(remaining)
+                .await
+            else {
+                table.apply(table.timeout_action());
+
+                interaction
+                    .edit_response(
+                        ctx,
+                        EditInteractionResponse::new()
+                            .embed(table_embed(table))
+                            .components(table_components(table)),
+                    )
+                    .await?;
+
+                break;
+            };
+
+            let custom_id = component.data.custom_id.as_str();
+
+            if custom_id == "poker_cards" {
+                let content = match table.seat(component.user.id) {
+                    Some(seat) => format!("Your cards: {} {}", seat.hole[0], seat.hole[1]),
+                    None => String::from("You are not seated at this table."),
+                };
+
+                ephemeral(ctx, &component, &content).await?;
+                continue;
+            }
+
+            if component.user.id != turn {
+                ephemeral(ctx, &component, "It's not your turn.").await?;
+                continue;
+            }
+
+            // Lobby buttons clicked on a stale client are not actions
+            let Ok(action) = custom_id.parse::<Action>() else {
+                component
+                    .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                    .await?;
+                continue;
+            };
+
+            table.apply(action);
+
+            component
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .embed(table_embed(table))
+                            .components(table_components(table)),
+                    ),
+                )
+                .await?;
+
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn session_row(msg: &Message, buy_in: i64, players: impl Iterator<Item = UserId>) -> SessionRow {
+    players.fold(SessionRow::new("poker", msg), |row, id| {
+        row.stake(id, buy_in)
+    })
+}
+
+fn lobby_embed(host: UserId, buy_in: i64, players: &[UserId]) -> CreateEmbed {
+    let big_blind = Table::big_blind_for(buy_in);
+
+    let seats = players
+        .iter()
+        .enumerate()
+        .map(|(i, id)| format!("{}. {}", i + 1, id.mention()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    CreateEmbed::new()
+        .title("Texas Hold'em")
+        .description(format!(
+            "{} opened a poker table!\n\nBuy-in: {} <:coin:{COIN}>\nBlinds: {} / {}\n\n**Seats ({}/{MAX_SEATS})**\n{seats}",
+            host.mention(),
+            buy_in.format(),
+            (big_blind / 2).format(),
+            big_blind.format(),
+            players.len()
+        ))
+        .colour(Colour::TEAL)
+}
+
+fn lobby_components() -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new("poker_join")
+            .label("Join")
+            .emoji('🪑')
+            .style(ButtonStyle::Secondary),
+        CreateButton::new("poker_start")
+            .label("Start")
+            .emoji('✅')
+            .style(ButtonStyle::Secondary),
+        CreateButton::new("poker_cancel")
+            .label("Cancel")
+            .emoji('❌')
+            .style(ButtonStyle::Secondary),
+    ])]
+}
+
+fn board(table: &Table) -> String {
+    if table.community.is_empty() {
+        return String::from("**Board:** No cards dealt yet");
+    }
+
+    let cards = table
+        .community
+        .iter()
+        .map(|card| card.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!("**Board:** {cards}")
+}
+
+fn table_embed(table: &Table) -> CreateEmbed {
+    let seats = table
+        .seats
+        .iter()
+        .enumerate()
+        .map(|(i, seat)| {
+            let marker = if !table.is_finished() && i == table.current {
+                "▶️ "
+            } else {
+                ""
+            };
+
+            let status = if seat.folded {
+                " | Folded"
+            } else if seat.is_all_in() {
+                " | All In"
+            } else {
+                ""
+            };
+
+            format!(
+                "{marker}{} - {} <:coin:{COIN}> | Bet: {}{status}",
+                seat.user_id.mention(),
+                seat.stack.format(),
+                seat.bet.format()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    CreateEmbed::new()
+        .title(format!("Texas Hold'em - {}", table.stage))
+        .description(format!(
+            "{}\n**Pot:** {} <:coin:{COIN}>\n\n{seats}\n\nTurns time out after {} seconds.",
+            board(table),
+            table.pot().format(),
+            TURN_TIMEOUT.as_secs()
+        ))
+        .colour(Colour::TEAL)
+}
+
+fn table_components(table: &Table) -> Vec<CreateActionRow> {
+    if table.is_finished() {
+        return Vec::new();
+    }
+
+    let to_call = table.to_call();
+
+    let call_label = if to_call == 0 {
+        String::from("Check")
+    } else {
+        format!("Call {}", to_call.format())
+    };
+
+    let actions = vec![
+        CreateButton::new("poker_fold")
+            .label("Fold")
+            .style(ButtonStyle::Danger),
+        CreateButton::new("poker_call")
+            .label(call_label)
+            .style(ButtonStyle::Secondary),
+        CreateButton::new("poker_raise")
+            .label(format!("Raise to {}", table.min_raise_to().format()))
+            .style(ButtonStyle::Secondary)
+            .disabled(!table.can_raise()),
+        CreateButton::new("poker_allin")
+            .label("All In")
+            .style(ButtonStyle::Primary),
+    ];
+
+    let cards = vec![
+        CreateButton::new("poker_cards")
+            .label("View Cards")
+            .emoji('🃏')
+            .style(ButtonStyle::Secondary),
+    ];
+
+    vec![
+        CreateActionRow::Buttons(actions),
+        CreateActionRow::Buttons(cards),
+    ]
+}
@@ -11,9 +11,11 @@ use sqlx::types::Json;
 use sqlx::{Database, FromRow, Pool};
 use zayden_core::FormatNum;
 
+use crate::events::{Dispatch, Event, PrestigeEvent, Subscriber};
 use crate::shop::LOTTO_TICKET;
 use crate::{
-    Commands, GamblingItem, MaxValues, Mining, Prestige, Result, SHOP_ITEMS, START_AMOUNT,
+    Coins, Commands, GamblingItem, Gems, LedgerManager, LedgerSource, MaxBet, MaxValues, Mining,
+    Prestige, Result, SHOP_ITEMS, START_AMOUNT, SessionManager, SessionRow,
 };
 
 #[async_trait]
@@ -21,11 +23,11 @@ pub trait PrestigeManager<Db: Database> {
     async fn miners(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<i64>>;
 
     async fn row(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         id: impl Into<UserId> + Send,
     ) -> sqlx::Result<Option<PrestigeRow>>;
 
-    async fn save(pool: &Pool<Db>, row: PrestigeRow) -> sqlx::Result<AnyQueryResult>;
+    async fn save(conn: &mut Db::Connection, row: PrestigeRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(FromRow, Default)]
@@ -34,6 +36,7 @@ pub struct PrestigeRow {
     pub coins: i64,
     pub gems: i64,
     pub stamina: i64,
+    pub level: Option<i32>,
     pub inventory: Option<Json<Vec<GamblingItem>>>,
     pub miners: i64,
     pub mines: i64,
@@ -186,23 +189,54 @@ impl Mining for PrestigeRow {
     }
 }
 
+impl Coins for PrestigeRow {
+    fn coins(&self) -> i64 {
+        self.coins
+    }
+
+    fn coins_mut(&mut self) -> &mut i64 {
+        &mut self.coins
+    }
+}
+
+impl Gems for PrestigeRow {
+    fn gems(&self) -> i64 {
+        self.gems
+    }
+
+    fn gems_mut(&mut self) -> &mut i64 {
+        &mut self.gems
+    }
+}
+
 impl Prestige for PrestigeRow {
     fn prestige(&self) -> i64 {
         self.prestige
     }
 }
 
+impl MaxBet for PrestigeRow {
+    fn level(&self) -> i32 {
+        self.level.unwrap_or_default()
+    }
+}
+
 impl Commands {
-    pub async fn prestige<Db: Database, Manager: PrestigeManager<Db>>(
+    pub async fn prestige<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        Manager: PrestigeManager<Db>,
+        SessionHandler: SessionManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+    >(
         ctx: &Context,
         interaction: &CommandInteraction,
         pool: &Pool<Db>,
     ) -> Result<()> {
         interaction.defer(ctx).await?;
 
-        let row = Manager::row(pool, interaction.user.id)
-            .await
-            .unwrap()
+        let row = Manager::row(&mut *pool.acquire().await?, interaction.user.id)
+            .await?
             .unwrap_or_default();
 
         let req_miners = row.req_miners();
@@ -246,26 +280,48 @@ impl Commands {
             .await
             .unwrap();
 
+        SessionHandler::save(
+            &mut *pool.acquire().await?,
+            SessionRow::new("prestige", &msg),
+        )
+        .await?;
+
         let mut stream = msg
             .await_component_interactions(ctx)
             .author_id(interaction.user.id)
             .timeout(Duration::from_secs(120))
             .stream();
 
-        if let Some(component) = stream.next().await {
+        let component = stream.next().await;
+
+        SessionHandler::delete(&mut *pool.acquire().await?, msg.id).await?;
+
+        if let Some(component) = component {
             if component.data.custom_id == "confirm" {
-                let mut row = Manager::row(pool, interaction.user.id)
-                    .await
-                    .unwrap()
-                    .unwrap();
+                let mut tx = pool.begin().await?;
+
+                let mut row = Manager::row(&mut *tx, interaction.user.id).await?.unwrap();
 
                 if row.miners < row.req_miners() {
                     return Ok(());
                 }
 
+                let (coins, gems) = (row.coins, row.gems);
                 row.do_prestige();
+                let (coins, gems) = (row.coins - coins, row.gems - gems);
+
+                let event = PrestigeEvent::new(interaction.user.id, row.prestige);
+                Dispatch::<Db, EventHandler>::new(&mut *tx)
+                    .fire(&mut row, Event::Prestige(event))
+                    .await?;
+
+                LedgerHandler::coins(&mut *tx, interaction.user.id, coins, LedgerSource::Prestige)
+                    .await?;
+                LedgerHandler::gems(&mut *tx, interaction.user.id, gems, LedgerSource::Prestige)
+                    .await?;
 
-                Manager::save(pool, row).await.unwrap();
+                Manager::save(&mut *tx, row).await?;
+                tx.commit().await?;
 
                 component
                     .create_response(
@@ -8,7 +8,11 @@ use serenity::all::{
 use sqlx::{Database, Pool, types::Json};
 use zayden_core::FormatNum;
 
-use crate::{COIN, Coins, GamblingItem, Gems, ItemInventory, MaxBet, Prestige, Result, ShopItem};
+use crate::achievements::ACHIEVEMENT_REGISTRY;
+use crate::{
+    AchievementRow, AchievementsManager, COIN, Coins, GamblingItem, GameStatsRow, Gems,
+    ItemInventory, MaxBet, Prestige, Result, ShopItem, StatsManager,
+};
 
 use super::Commands;
 
@@ -139,7 +143,12 @@ impl From<ProfileRow> for CreateEmbed {
 }
 
 impl Commands {
-    pub async fn profile<Db: Database, Manager: ProfileManager<Db>>(
+    pub async fn profile<
+        Db: Database,
+        Manager: ProfileManager<Db>,
+        StatsHandler: StatsManager<Db>,
+        AchievementsHandler: AchievementsManager<Db>,
+    >(
         ctx: &Context,
         interaction: &CommandInteraction,
         mut options: Vec<ResolvedOption<'_>>,
@@ -165,8 +174,25 @@ impl Commands {
             embed = embed.thumbnail(avatar);
         }
 
+        let achievements = AchievementsHandler::rows(&mut *pool.acquire().await?, user.id).await?;
+        embed = embed.field(
+            format!(
+                "Achievements ({}/{})",
+                achievements.iter().filter(|row| row.is_unlocked()).count(),
+                ACHIEVEMENT_REGISTRY.iter().count()
+            ),
+            achievements_str(&achievements),
+            false,
+        );
+
+        let mut stats = StatsHandler::rows(pool, user.id).await?;
+        stats.sort_by_key(|row| -row.played);
+
         interaction
-            .edit_response(ctx, EditInteractionResponse::new().embed(embed))
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new().embeds(vec![embed, stats_embed(stats)]),
+            )
             .await
             .unwrap();
 
@@ -175,7 +201,7 @@ impl Commands {
 
     pub fn register_profile() -> CreateCommand {
         CreateCommand::new("profile")
-            .description("Show your coins, level and items")
+            .description("Show your coins, level, items, achievements and game stats")
             .add_option(CreateCommandOption::new(
                 CommandOptionType::User,
                 "user",
@@ -183,3 +209,47 @@ impl Commands {
             ))
     }
 }
+
+fn achievements_str(achievements: &[AchievementRow]) -> String {
+    let badges = ACHIEVEMENT_REGISTRY
+        .iter()
+        .filter(|definition| {
+            achievements
+                .iter()
+                .any(|row| row.achievement_id == definition.id && row.is_unlocked())
+        })
+        .map(|definition| definition.badge())
+        .collect::<Vec<_>>();
+
+    if badges.is_empty() {
+        String::from("No achievements unlocked yet")
+    } else {
+        badges.join("\n")
+    }
+}
+
+fn stats_embed(stats: Vec<GameStatsRow>) -> CreateEmbed {
+    let embed = CreateEmbed::new().title("Game Stats").colour(Colour::TEAL);
+
+    if stats.is_empty() {
+        return embed.description("No games played");
+    }
+
+    stats.into_iter().fold(embed, |embed, row| {
+        embed.field(
+            &row.game_id,
+            format!(
+                "Played: {} ({}W / {}L)\nWagered: {}\nWon: {}\nNet: {}\nBiggest Win: {}\nLongest Streak: {}",
+                row.played.format(),
+                row.wins.format(),
+                row.losses.format(),
+                row.wagered.format(),
+                row.won.format(),
+                row.net_profit().format(),
+                row.biggest_win.format(),
+                row.longest_streak.format()
+            ),
+            true,
+        )
+    })
+}
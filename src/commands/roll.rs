@@ -1,14 +1,18 @@
+use std::cmp::Ordering;
+
 use serenity::all::{
-    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    EditInteractionResponse, ResolvedOption, ResolvedValue,
+    Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    EditInteractionResponse, Mentionable, ResolvedOption, ResolvedValue, UserId,
 };
 use sqlx::{Database, Pool};
-use zayden_core::parse_options;
+use zayden_core::{FormatNum, parse_options};
 
-use crate::events::{Dispatch, Event, GameEvent};
+use crate::duel::Duel;
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
 use crate::utils::{GameResult, game_embed};
 use crate::{
-    Coins, EffectsManager, Error, GameCache, GameManager, GameRow, GoalsManager, Result, VerifyBet,
+    ActiveGames, COIN, Coins, EffectsManager, Error, GamblingConfig, GameCache, GameManager,
+    GameRow, LedgerManager, LedgerSource, Result, SessionManager, VerifyBet,
 };
 
 use super::Commands;
@@ -16,9 +20,11 @@ use super::Commands;
 impl Commands {
     pub async fn roll<
         Db: Database,
-        GoalHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         EffectsHandler: EffectsManager<Db> + Send,
         GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -35,54 +41,88 @@ impl Commands {
 
         let n_sides = dice.parse::<i64>().unwrap();
 
+        let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
+            unreachable!("bet option is required")
+        };
+
+        if let Some(ResolvedValue::User(opponent, _)) = options.remove("opponent") {
+            return duel::<Db, EventHandler, GameHandler, LedgerHandler, SessionHandler>(
+                ctx,
+                interaction,
+                pool,
+                n_sides,
+                opponent.id,
+                bet,
+            )
+            .await;
+        }
+
         let Some(ResolvedValue::Integer(prediction)) = options.remove("prediction") else {
-            unreachable!("prediction option is required")
+            return Err(Error::InvalidPrediction);
         };
 
         verify_prediction(prediction, 1, n_sides)?;
 
-        let mut row = GameHandler::row(pool, interaction.user.id)
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
             .await
             .unwrap()
             .unwrap_or_else(|| GameRow::new(interaction.user.id));
 
         GameCache::can_play(ctx, interaction.user.id).await?;
-
-        let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
-            unreachable!("bet option is required")
-        };
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "roll").await?;
 
         row.verify_bet(bet)?;
         row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("roll"),
+        )
+        .await?;
 
         let roll = rand::random_range(1..=n_sides);
 
-        let (title, mut payout) = if roll == prediction {
+        let (title, payout) = if roll == prediction {
             ("🎲 Dice Roll 🎲 - You Won!", bet * n_sides)
         } else {
             ("🎲 Dice Roll 🎲 - You Lost!", 0)
         };
 
-        Dispatch::<Db, GoalHandler>::new(pool)
+        let (payout, effects) = EffectsHandler::payout(
+            &mut *tx,
+            interaction.user.id,
+            bet,
+            payout,
+            roll == prediction,
+        )
+        .await;
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("roll"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
             .fire(
                 &mut row,
-                Event::Game(GameEvent::new(
-                    "roll",
-                    interaction.user.id,
-                    bet,
-                    roll == prediction,
-                )),
+                Event::Game(
+                    GameEvent::new("roll", interaction.user.id, bet, roll == prediction)
+                        .set_payout(payout)
+                        .set_effects(effects),
+                ),
             )
             .await?;
 
-        payout = EffectsHandler::payout(pool, interaction.user.id, bet, payout, roll == prediction)
-            .await;
-
-        row.add_coins(payout);
-
         let coins = row.coins();
 
-        GameHandler::save(pool, row).await.unwrap();
+        GameHandler::save(&mut *tx, row).await.unwrap();
+        tx.commit().await?;
         GameCache::update(ctx, interaction.user.id).await;
 
         let embed = game_embed(
@@ -120,19 +160,100 @@ impl Commands {
                 .add_string_choice("20-sides", "20")
                 .required(true),
             )
-            .add_option(
-                CreateCommandOption::new(
-                    CommandOptionType::Integer,
-                    "prediction",
-                    "What number will the dice land on?",
-                )
-                .required(true),
-            )
             .add_option(
                 CreateCommandOption::new(CommandOptionType::Integer, "bet", "Roll the dice")
                     .required(true),
             )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "prediction",
+                "What number will the dice land on? Not used when challenging a player",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "opponent",
+                "Challenge another player, the highest roll wins",
+            ))
+    }
+}
+
+async fn duel<
+    Db: Database,
+    EventHandler: Subscriber<Db>,
+    GameHandler: GameManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    pool: &Pool<Db>,
+    n_sides: i64,
+    opponent: UserId,
+    bet: i64,
+) -> Result<()> {
+    let mut duel = Duel::new(
+        "roll",
+        "🎲 Dice Duel 🎲",
+        interaction.user.id,
+        opponent,
+        bet,
+    )
+    .rake(GamblingConfig::get(ctx).await.duel_rake);
+
+    let terms = format!("a {n_sides}-sided dice duel");
+
+    if !duel
+        .challenge::<Db, GameHandler, LedgerHandler, SessionHandler>(ctx, interaction, pool, &terms)
+        .await?
+    {
+        return Ok(());
     }
+
+    let challenger_roll = rand::random_range(1..=n_sides);
+    let opponent_roll = rand::random_range(1..=n_sides);
+
+    let winner = match challenger_roll.cmp(&opponent_roll) {
+        Ordering::Greater => Some(duel.challenger),
+        Ordering::Less => Some(duel.opponent),
+        Ordering::Equal => None,
+    };
+
+    duel.settle::<Db, EventHandler, GameHandler, LedgerHandler, SessionHandler>(ctx, pool, winner)
+        .await?;
+
+    let (result, colour) = match winner {
+        Some(winner) => (
+            format!(
+                "{} wins {} <:coin:{COIN}> 🎉",
+                winner.mention(),
+                duel.winnings().format()
+            ),
+            Colour::DARK_GREEN,
+        ),
+        None => (
+            String::from("It's a draw! Both bets have been returned."),
+            Colour::ORANGE,
+        ),
+    };
+
+    let embed = duel
+        .embed(format!(
+            "{} rolled 🎲 **{challenger_roll}**\n{} rolled 🎲 **{opponent_roll}**\n\n{result}",
+            duel.challenger.mention(),
+            duel.opponent.mention(),
+        ))
+        .colour(colour);
+
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
 }
 
 fn verify_prediction(prediction: i64, min: i64, max: i64) -> Result<()> {
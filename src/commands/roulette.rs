@@ -0,0 +1,438 @@
+use std::str::FromStr;
+
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, CreateEmbedFooter, EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+use sqlx::{Database, Pool};
+use zayden_core::{FormatNum, parse_options};
+
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::utils::signed;
+use crate::{
+    ActiveGames, COIN, Coins, EffectsManager, Error, GameCache, GameManager, GameRow,
+    LedgerManager, LedgerSource, MaxBet, Result, VerifyBet,
+};
+
+use super::Commands;
+
+impl Commands {
+    pub async fn roulette<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        EffectsHandler: EffectsManager<Db> + Send,
+        GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+    >(
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        interaction.defer(ctx).await.unwrap();
+
+        let mut options = parse_options(options);
+
+        let Some(ResolvedValue::String(bets)) = options.remove("bets") else {
+            unreachable!("bets is required")
+        };
+
+        let wheel = match options.remove("wheel") {
+            Some(ResolvedValue::String(wheel)) => wheel.parse::<Wheel>().unwrap(),
+            _ => Wheel::European,
+        };
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        let bets = parse_bets(bets, wheel, row.max_bet())?;
+
+        let total_bet = bets
+            .iter()
+            .try_fold(0i64, |total, bet| total.checked_add(bet.amount))
+            .ok_or(Error::InvalidAmount)?;
+
+        GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "roulette").await?;
+        row.verify_bet(total_bet)?;
+        row.bet(total_bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -total_bet,
+            LedgerSource::Game("roulette"),
+        )
+        .await?;
+
+        let pocket = wheel.spin();
+
+        let payout = bets
+            .iter()
+            .filter(|bet| bet.numbers.contains(&pocket))
+            .try_fold(0i64, |total, bet| {
+                bet.amount
+                    .checked_mul(bet.bet_type.odds + 1)
+                    .and_then(|payout| total.checked_add(payout))
+            })
+            .ok_or(Error::InvalidAmount)?;
+        let winner = payout > total_bet;
+
+        let (payout, effects) =
+            EffectsHandler::payout(&mut *tx, interaction.user.id, total_bet, payout, winner).await;
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("roulette"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Game(
+                    GameEvent::new("roulette", interaction.user.id, total_bet, winner)
+                        .set_payout(payout)
+                        .set_effects(effects),
+                ),
+            )
+            .await?;
+
+        let coins = row.coins();
+
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
+        GameCache::update(ctx, interaction.user.id).await;
+
+        let (title, colour) = if winner {
+            ("🎡 Roulette 🎡 - You Won!", Colour::DARK_GREEN)
+        } else {
+            ("🎡 Roulette 🎡 - You Lost!", Colour::RED)
+        };
+
+        let bets = bets
+            .iter()
+            .map(|bet| {
+                let result = if bet.numbers.contains(&pocket) {
+                    format!("✅ +{}", (bet.amount * bet.bet_type.odds).format())
+                } else {
+                    String::from("❌")
+                };
+
+                format!(
+                    "{} ({}) - {} <:coin:{COIN}> {result}",
+                    bet.bet_type.name,
+                    bet.label,
+                    bet.amount.format()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let desc = format!(
+            "Your bet: {} <:coin:{COIN}>\n\n**Ball landed on:** {} {}\n\n{bets}\n\nPayout: {} ({})\nYour coins: {}",
+            total_bet.format(),
+            pocket_emoji(pocket),
+            pocket_name(pocket),
+            payout.format(),
+            signed(payout - total_bet),
+            coins.format()
+        );
+
+        let embed = CreateEmbed::new()
+            .title(title)
+            .description(desc)
+            .footer(CreateEmbedFooter::new(wheel.to_string()))
+            .colour(colour);
+
+        interaction
+            .edit_response(ctx, EditInteractionResponse::new().embed(embed))
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    pub fn register_roulette() -> CreateCommand {
+        CreateCommand::new("roulette")
+            .description("Spin the roulette wheel")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "bets",
+                    "Semicolon separated bets, e.g. `red 1,000; 17 100; 1-2 200; dozen2 100`",
+                )
+                .required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "wheel", "The wheel to use")
+                    .add_string_choice("European (single zero)", "european")
+                    .add_string_choice("American (double zero)", "american"),
+            )
+    }
+}
+
+const DOUBLE_ZERO: u8 = 37;
+
+const RED_NUMBERS: [u8; 18] = [
+    1, 3, 5, 7, 9, 12, 14, 16, 18, 19, 21, 23, 25, 27, 30, 32, 34, 36,
+];
+
+#[derive(Clone, Copy)]
+enum Wheel {
+    European,
+    American,
+}
+
+impl Wheel {
+    fn spin(&self) -> u8 {
+        match self {
+            Self::European => rand::random_range(0..=36),
+            Self::American => rand::random_range(0..=DOUBLE_ZERO),
+        }
+    }
+}
+
+impl std::fmt::Display for Wheel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::European => write!(f, "European Wheel"),
+            Self::American => write!(f, "American Wheel"),
+        }
+    }
+}
+
+impl FromStr for Wheel {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "european" => Ok(Self::European),
+            "american" => Ok(Self::American),
+            _ => Err(()),
+        }
+    }
+}
+
+struct BetType {
+    name: &'static str,
+    odds: i64,
+}
+
+impl BetType {
+    const fn new(name: &'static str, odds: i64) -> Self {
+        Self { name, odds }
+    }
+}
+
+static STRAIGHT: BetType = BetType::new("Straight", 35);
+static SPLIT: BetType = BetType::new("Split", 17);
+static STREET: BetType = BetType::new("Street", 11);
+static CORNER: BetType = BetType::new("Corner", 8);
+static DOZEN: BetType = BetType::new("Dozen", 2);
+static COLUMN: BetType = BetType::new("Column", 2);
+static RED: BetType = BetType::new("Red", 1);
+static BLACK: BetType = BetType::new("Black", 1);
+static ODD: BetType = BetType::new("Odd", 1);
+static EVEN: BetType = BetType::new("Even", 1);
+static LOW: BetType = BetType::new("Low", 1);
+static HIGH: BetType = BetType::new("High", 1);
+
+struct RouletteBet {
+    bet_type: &'static BetType,
+    label: String,
+    numbers: Vec<u8>,
+    amount: i64,
+}
+
+impl RouletteBet {
+    fn parse(s: &str, wheel: Wheel, max_bet: i64) -> Result<Self> {
+        let invalid = || Error::InvalidBet(s.to_string());
+
+        let mut parts = s.split_whitespace();
+        let spec = parts.next().ok_or_else(invalid)?.to_lowercase();
+        let amount = parts
+            .next()
+            .and_then(|amount| amount.replace(',', "").parse::<i64>().ok())
+            .ok_or_else(invalid)?;
+
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        if amount < 1 {
+            return Err(Error::MinimumBetAmount(1));
+        }
+
+        if amount > max_bet {
+            return Err(Error::MaximumBetAmount(max_bet));
+        }
+
+        let table = 1..=36u8;
+
+        let (bet_type, numbers): (&BetType, Vec<u8>) = match spec.as_str() {
+            "red" => (&RED, RED_NUMBERS.to_vec()),
+            "black" => (&BLACK, table.filter(|n| !RED_NUMBERS.contains(n)).collect()),
+            "odd" => (&ODD, table.filter(|n| n % 2 == 1).collect()),
+            "even" => (&EVEN, table.filter(|n| n % 2 == 0).collect()),
+            "low" => (&LOW, (1..=18).collect()),
+            "high" => (&HIGH, (19..=36).collect()),
+            "dozen1" => (&DOZEN, (1..=12).collect()),
+            "dozen2" => (&DOZEN, (13..=24).collect()),
+            "dozen3" => (&DOZEN, (25..=36).collect()),
+            "column1" => (&COLUMN, table.filter(|n| n % 3 == 1).collect()),
+            "column2" => (&COLUMN, table.filter(|n| n % 3 == 2).collect()),
+            "column3" => (&COLUMN, table.filter(|n| n % 3 == 0).collect()),
+            spec => {
+                let mut numbers = spec
+                    .split('-')
+                    .map(|n| parse_pocket(n, wheel))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(invalid)?;
+                numbers.sort_unstable();
+                numbers.dedup();
+
+                let bet_type = match numbers[..] {
+                    [_] => &STRAIGHT,
+                    [a, b] if is_split(a, b, wheel) => &SPLIT,
+                    [a, b, c] if a % 3 == 1 && b == a + 1 && c == a + 2 => &STREET,
+                    [a, b, c, d]
+                        if a % 3 != 0 && b == a + 1 && c == a + 3 && d == a + 4 && d <= 36 =>
+                    {
+                        &CORNER
+                    }
+                    _ => return Err(invalid()),
+                };
+
+                (bet_type, numbers)
+            }
+        };
+
+        let label = if bet_type.odds > 2 {
+            numbers
+                .iter()
+                .map(|n| pocket_name(*n))
+                .collect::<Vec<_>>()
+                .join("-")
+        } else {
+            spec
+        };
+
+        Ok(Self {
+            bet_type,
+            label,
+            numbers,
+            amount,
+        })
+    }
+}
+
+// Amounts may hold thousands separators, e.g. `red 1,000`
+fn parse_bets(s: &str, wheel: Wheel, max_bet: i64) -> Result<Vec<RouletteBet>> {
+    s.split(';')
+        .map(str::trim)
+        .filter(|bet| !bet.is_empty())
+        .map(|bet| RouletteBet::parse(bet, wheel, max_bet))
+        .collect()
+}
+
+fn parse_pocket(s: &str, wheel: Wheel) -> Option<u8> {
+    match (s, wheel) {
+        ("00", Wheel::American) => Some(DOUBLE_ZERO),
+        ("00", Wheel::European) => None,
+        (s, _) => s.parse::<u8>().ok().filter(|n| *n <= 36),
+    }
+}
+
+// `a` is always the lower number, so 00 (37) can only appear as `b`. On the
+// American layout 0 only borders 1 and 2, and 00 borders 2 and 3.
+fn is_split(a: u8, b: u8, wheel: Wheel) -> bool {
+    match (a, b, wheel) {
+        (0, 1..=3, Wheel::European) => true,
+        (0, 1..=2, Wheel::American) => true,
+        (0 | 2..=3, DOUBLE_ZERO, Wheel::American) => true,
+        (1..=36, 1..=36, _) => b - a == 3 || (b - a == 1 && a % 3 != 0),
+        _ => false,
+    }
+}
+
+fn pocket_name(pocket: u8) -> String {
+    if pocket == DOUBLE_ZERO {
+        String::from("00")
+    } else {
+        pocket.to_string()
+    }
+}
+
+fn pocket_emoji(pocket: u8) -> char {
+    if pocket == 0 || pocket == DOUBLE_ZERO {
+        '🟢'
+    } else if RED_NUMBERS.contains(&pocket) {
+        '🔴'
+    } else {
+        '⚫'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_BET: i64 = 10_000;
+
+    fn bet(s: &str, wheel: Wheel) -> Option<&'static str> {
+        RouletteBet::parse(s, wheel, MAX_BET)
+            .ok()
+            .map(|bet| bet.bet_type.name)
+    }
+
+    #[test]
+    fn splits_with_zero_follow_the_wheel() {
+        assert_eq!(bet("0-1 100", Wheel::European), Some("Split"));
+        assert_eq!(bet("0-3 100", Wheel::European), Some("Split"));
+        assert_eq!(bet("0-3 100", Wheel::American), None);
+        assert_eq!(bet("0-00 100", Wheel::American), Some("Split"));
+        assert_eq!(bet("2-00 100", Wheel::American), Some("Split"));
+        assert_eq!(bet("3-00 100", Wheel::American), Some("Split"));
+        assert_eq!(bet("1-00 100", Wheel::American), None);
+    }
+
+    #[test]
+    fn double_zero_is_american_only() {
+        assert_eq!(bet("00 100", Wheel::American), Some("Straight"));
+        assert_eq!(bet("00 100", Wheel::European), None);
+        assert_eq!(bet("0-00 100", Wheel::European), None);
+    }
+
+    #[test]
+    fn corners_do_not_wrap_past_column_three() {
+        assert_eq!(bet("2-3-5-6 100", Wheel::European), Some("Corner"));
+        assert_eq!(bet("32-33-35-36 100", Wheel::European), Some("Corner"));
+        assert_eq!(bet("3-4-6-7 100", Wheel::European), None);
+        assert_eq!(bet("33-34-36-37 100", Wheel::American), None);
+    }
+
+    #[test]
+    fn amounts_are_capped_at_the_max_bet() {
+        assert!(RouletteBet::parse("red 10,000", Wheel::European, MAX_BET).is_ok());
+        assert!(matches!(
+            RouletteBet::parse("red 10,001", Wheel::European, MAX_BET),
+            Err(Error::MaximumBetAmount(MAX_BET))
+        ));
+    }
+
+    #[test]
+    fn separators_skip_empty_entries() {
+        let bets = parse_bets("red 1,000; 17 100;; ", Wheel::European, MAX_BET).unwrap();
+
+        assert_eq!(
+            bets.iter().map(|bet| bet.amount).collect::<Vec<_>>(),
+            [1_000, 100]
+        );
+        assert!(parse_bets("red 1,000, black 100", Wheel::European, MAX_BET).is_err());
+    }
+}
@@ -1,16 +1,21 @@
+use std::time::Duration;
 use std::{fmt::Display, str::FromStr};
 
+use futures::StreamExt;
 use rand::seq::IndexedRandom;
 use serenity::all::{
-    Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    CreateEmbed, EditInteractionResponse, ResolvedOption, ResolvedValue,
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, Context, CreateActionRow,
+    CreateButton, CreateCommand, CreateCommandOption, CreateEmbed, EditInteractionResponse,
+    Mentionable, ResolvedOption, ResolvedValue, UserId,
 };
 use sqlx::{Database, Pool};
 use zayden_core::{FormatNum, parse_options};
 
-use crate::events::{Dispatch, Event, GameEvent};
+use crate::duel::{Duel, ephemeral};
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
 use crate::{
-    COIN, Coins, EffectsManager, GameCache, GameManager, GameRow, GoalsManager, Result, VerifyBet,
+    ActiveGames, COIN, Coins, EffectsManager, Error, GamblingConfig, GameCache, GameManager,
+    GameRow, LedgerManager, LedgerSource, Result, SessionManager, VerifyBet,
 };
 
 use super::Commands;
@@ -18,9 +23,11 @@ use super::Commands;
 impl Commands {
     pub async fn rps<
         Db: Database,
-        GoalHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         EffectsHandler: EffectsManager<Db> + Send,
         GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -31,27 +38,53 @@ impl Commands {
 
         let mut options = parse_options(options);
 
-        let Some(ResolvedValue::String(selection)) = options.remove("selection") else {
-            unreachable!("selection is required")
-        };
-        let user_choice = selection.parse::<RPSChoice>().unwrap();
-
         let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
             unreachable!("bet is required")
         };
 
-        let mut row = GameHandler::row(pool, interaction.user.id)
+        if let Some(ResolvedValue::User(opponent, _)) = options.remove("opponent") {
+            let best_of = match options.remove("best_of") {
+                Some(ResolvedValue::String(best_of)) => best_of.parse().unwrap(),
+                _ => 1,
+            };
+
+            return duel::<Db, EventHandler, GameHandler, LedgerHandler, SessionHandler>(
+                ctx,
+                interaction,
+                pool,
+                opponent.id,
+                bet,
+                best_of,
+            )
+            .await;
+        }
+
+        let Some(ResolvedValue::String(selection)) = options.remove("selection") else {
+            return Err(Error::InvalidPrediction);
+        };
+        let user_choice = selection.parse::<RPSChoice>().unwrap();
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
             .await?
             .unwrap_or_else(|| GameRow::new(interaction.user.id));
 
         GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "rps").await?;
         row.verify_bet(bet)?;
         row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("rps"),
+        )
+        .await?;
 
         let computer_choice = *CHOICES.choose(&mut rand::rng()).unwrap();
         let winner = user_choice.winner(&computer_choice);
 
-        let mut payout = if winner == Some(true) {
+        let payout = if winner == Some(true) {
             bet * 2
         } else if winner.is_none() {
             bet
@@ -59,27 +92,40 @@ impl Commands {
             0
         };
 
-        Dispatch::<Db, GoalHandler>::new(pool)
+        let (payout, effects) = EffectsHandler::payout(
+            &mut *tx,
+            interaction.user.id,
+            bet,
+            payout,
+            winner == Some(true),
+        )
+        .await;
+
+        let event = match winner {
+            Some(win) => GameEvent::new("rps", interaction.user.id, bet, win),
+            None => GameEvent::new_draw("rps", interaction.user.id, bet),
+        };
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("rps"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
             .fire(
                 &mut row,
-                Event::Game(GameEvent::new(
-                    "rps",
-                    interaction.user.id,
-                    bet,
-                    winner == Some(true),
-                )),
+                Event::Game(event.set_payout(payout).set_effects(effects)),
             )
             .await?;
 
-        payout =
-            EffectsHandler::payout(pool, interaction.user.id, bet, payout, winner == Some(true))
-                .await;
-
-        row.add_coins(payout);
-
         let coins = row.coins();
 
-        GameHandler::save(pool, row).await?;
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
         GameCache::update(ctx, interaction.user.id).await;
 
         let title = if winner == Some(true) {
@@ -123,25 +169,286 @@ impl Commands {
 
     pub fn register_rps() -> CreateCommand {
         CreateCommand::new("rps")
-            .description("Play a game of rock paper scissors against the bot")
+            .description("Play a game of rock paper scissors against the bot or another player")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
+                    .required(true),
+            )
             .add_option(
                 CreateCommandOption::new(
                     CommandOptionType::String,
                     "selection",
-                    "Your choice of Rock, Paper or Scissors",
+                    "Your choice of Rock, Paper or Scissors against the bot",
                 )
-                .required(true)
                 .add_string_choice("Rock", "rock")
                 .add_string_choice("Paper", "paper")
                 .add_string_choice("Scissors", "scissors"),
             )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "opponent",
+                "Challenge another player, picks are made in secret",
+            ))
             .add_option(
-                CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
-                    .required(true),
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "best_of",
+                    "The number of rounds when challenging a player",
+                )
+                .add_string_choice("Single round", "1")
+                .add_string_choice("Best of 3", "3")
+                .add_string_choice("Best of 5", "5"),
             )
     }
 }
 
+const TITLE: &str = "Rock 🪨 Paper 🗞️ Scissors ✂";
+
+async fn duel<
+    Db: Database,
+    EventHandler: Subscriber<Db>,
+    GameHandler: GameManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    pool: &Pool<Db>,
+    opponent: UserId,
+    bet: i64,
+    best_of: usize,
+) -> Result<()> {
+    let mut duel = Duel::new("rps", TITLE, interaction.user.id, opponent, bet)
+        .rake(GamblingConfig::get(ctx).await.duel_rake);
+
+    let terms = if best_of == 1 {
+        String::from("a game of rock paper scissors")
+    } else {
+        format!("a best of {best_of} game of rock paper scissors")
+    };
+
+    if !duel
+        .challenge::<Db, GameHandler, LedgerHandler, SessionHandler>(ctx, interaction, pool, &terms)
+        .await?
+    {
+        return Ok(());
+    }
+
+    let mut game = RpsDuel::new(duel.players(), best_of);
+
+    while !game.is_finished() {
+        let msg = interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(game.embed(&duel))
+                    .components(pick_buttons()),
+            )
+            .await?;
+
+        let mut stream = msg
+            .await_component_interactions(ctx)
+            .timeout(Duration::from_secs(60))
+            .stream();
+
+        while let Some(component) = stream.next().await {
+            let Some(player) = game.players.iter().position(|id| *id == component.user.id) else {
+                ephemeral(ctx, &component, "You're not playing in this game.").await?;
+                continue;
+            };
+
+            if game.picks[player].is_some() {
+                ephemeral(ctx, &component, "You've already picked this round.").await?;
+                continue;
+            }
+
+            let choice = component
+                .data
+                .custom_id
+                .strip_prefix("rps_")
+                .and_then(|choice| choice.parse::<RPSChoice>().ok())
+                .expect("Invalid custom id");
+
+            game.picks[player] = Some(choice);
+
+            let content = format!("You picked {} {choice}", choice.as_emoji());
+            ephemeral(ctx, &component, &content).await?;
+
+            if game.picks.iter().all(Option::is_some) {
+                break;
+            }
+
+            interaction
+                .edit_response(ctx, EditInteractionResponse::new().embed(game.embed(&duel)))
+                .await?;
+        }
+
+        if !game.reveal() {
+            break;
+        }
+    }
+
+    let winner = game.winner();
+
+    duel.settle::<Db, EventHandler, GameHandler, LedgerHandler, SessionHandler>(ctx, pool, winner)
+        .await?;
+
+    let (result, colour) = match winner {
+        Some(winner) => (
+            format!(
+                "{} wins {} <:coin:{COIN}> 🎉",
+                winner.mention(),
+                duel.winnings().format()
+            ),
+            Colour::DARK_GREEN,
+        ),
+        None => (
+            String::from("No winner! Both bets have been returned."),
+            Colour::DARKER_GREY,
+        ),
+    };
+
+    let embed = duel
+        .embed(format!("{}\n\n{result}", game.rounds_str(&duel)))
+        .colour(colour);
+
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn pick_buttons() -> Vec<CreateActionRow> {
+    let buttons = CHOICES
+        .iter()
+        .map(|choice| {
+            CreateButton::new(format!("rps_{}", choice.to_string().to_lowercase()))
+                .label(choice.to_string())
+                .emoji(choice.as_emoji().chars().next().unwrap())
+                .style(ButtonStyle::Secondary)
+        })
+        .collect();
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+struct RpsDuel {
+    players: [UserId; 2],
+    best_of: usize,
+    score: [usize; 2],
+    picks: [Option<RPSChoice>; 2],
+    rounds: Vec<[RPSChoice; 2]>,
+    // Set when a round times out before both players picked
+    forfeit: Option<Option<UserId>>,
+}
+
+impl RpsDuel {
+    fn new(players: [UserId; 2], best_of: usize) -> Self {
+        Self {
+            players,
+            best_of,
+            score: [0, 0],
+            picks: [None, None],
+            rounds: Vec::new(),
+            forfeit: None,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.forfeit.is_some() || self.score.iter().any(|wins| *wins > self.best_of / 2)
+    }
+
+    /// Scores the round once both picks are in. Returns false if a player
+    /// failed to pick, in which case the player who did pick wins the game.
+    fn reveal(&mut self) -> bool {
+        let picks = std::mem::take(&mut self.picks);
+
+        let [Some(p1), Some(p2)] = picks else {
+            self.forfeit = Some(match picks {
+                [Some(_), None] => Some(self.players[0]),
+                [None, Some(_)] => Some(self.players[1]),
+                _ => None,
+            });
+
+            return false;
+        };
+
+        match p1.winner(&p2) {
+            Some(true) => self.score[0] += 1,
+            Some(false) => self.score[1] += 1,
+            None => {}
+        }
+
+        self.rounds.push([p1, p2]);
+
+        true
+    }
+
+    fn winner(&self) -> Option<UserId> {
+        if let Some(forfeit) = self.forfeit {
+            return forfeit;
+        }
+
+        self.score
+            .iter()
+            .position(|wins| *wins > self.best_of / 2)
+            .map(|i| self.players[i])
+    }
+
+    fn rounds_str(&self, duel: &Duel) -> String {
+        let mut s = format!(
+            "{} **{}** - **{}** {}",
+            duel.challenger.mention(),
+            self.score[0],
+            self.score[1],
+            duel.opponent.mention()
+        );
+
+        for (i, [p1, p2]) in self.rounds.iter().enumerate() {
+            s.push_str(&format!(
+                "\nRound {}: {} vs {}",
+                i + 1,
+                p1.as_emoji(),
+                p2.as_emoji()
+            ));
+        }
+
+        s
+    }
+
+    fn embed(&self, duel: &Duel) -> CreateEmbed {
+        let status = self
+            .players
+            .iter()
+            .zip(self.picks.iter())
+            .map(|(id, pick)| {
+                let status = if pick.is_some() { "✅" } else { "⏳" };
+                format!("{status} {}", id.mention())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let best_of = if self.best_of == 1 {
+            String::new()
+        } else {
+            format!(" (Best of {})", self.best_of)
+        };
+
+        duel.embed(format!(
+            "{}\n\n**Round {}{best_of}** - Pick your move!\n{status}",
+            self.rounds_str(duel),
+            self.rounds.len() + 1
+        ))
+        .colour(Colour::TEAL)
+    }
+}
+
 const CHOICES: [RPSChoice; 3] = [RPSChoice::Rock, RPSChoice::Paper, RPSChoice::Scissors];
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -7,10 +7,10 @@ use sqlx::any::AnyQueryResult;
 use sqlx::{Database, Pool};
 use zayden_core::{FormatNum, parse_options};
 
-use crate::events::{Dispatch, Event, SendEvent};
+use crate::events::{Dispatch, Event, ReceiveEvent, SendEvent, Subscriber};
 use crate::{
-    COIN, Coins, Commands, Error, Gems, GoalsManager, MaxBet, Prestige, Result, ShopCurrency,
-    Stamina, StaminaManager,
+    COIN, Coins, Commands, Error, Gems, LedgerManager, LedgerSource, MaxBet, Prestige, Result,
+    ShopCurrency, Stamina, StaminaManager,
 };
 
 pub struct SendRow {
@@ -81,23 +81,27 @@ impl Prestige for SendRow {
 
 #[async_trait]
 pub trait SendManager<Db: Database> {
-    async fn row(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<SendRow>>;
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<SendRow>>;
 
     async fn add_coins(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         id: impl Into<UserId> + Send,
         amount: i64,
     ) -> sqlx::Result<AnyQueryResult>;
 
-    async fn save(pool: &Pool<Db>, row: SendRow) -> sqlx::Result<AnyQueryResult>;
+    async fn save(conn: &mut Db::Connection, row: SendRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 impl Commands {
     pub async fn send<
         Db: Database,
         StaminaHandler: StaminaManager<Db>,
-        GoalHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         SendHandler: SendManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -124,7 +128,19 @@ impl Commands {
             return Err(Error::NegativeAmount);
         }
 
-        let mut row = match SendHandler::row(pool, interaction.user.id).await.unwrap() {
+        let mut tx = pool.begin().await?;
+
+        // Rows are locked in ascending id order, so two players sending to
+        // each other at once can't deadlock. Otherwise the recipient's lock is
+        // taken by the increment below.
+        if recipient.id < interaction.user.id {
+            SendHandler::row(&mut *tx, recipient.id).await?;
+        }
+
+        let mut row = match SendHandler::row(&mut *tx, interaction.user.id)
+            .await
+            .unwrap()
+        {
             Some(row) => row,
             None => SendRow::new(interaction.user.id),
         };
@@ -145,21 +161,37 @@ impl Commands {
 
         *row.coins_mut() -= amount;
 
-        SendHandler::add_coins(pool, recipient.id, amount).await?;
+        SendHandler::add_coins(&mut *tx, recipient.id, amount).await?;
+        LedgerHandler::coins(&mut *tx, interaction.user.id, -amount, LedgerSource::Send).await?;
+        LedgerHandler::coins(&mut *tx, recipient.id, amount, LedgerSource::Send).await?;
+
+        // Read after the increment, whose update holds the row lock until the
+        // transaction ends
+        let mut recipient_row = SendHandler::row(&mut *tx, recipient.id)
+            .await?
+            .unwrap_or_else(|| SendRow::new(recipient.id));
 
         row.done_work();
 
         let stamina = row.stamina_str();
 
-        Dispatch::<Db, GoalHandler>::new(pool)
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
             .fire(
                 &mut row,
                 Event::Send(SendEvent::new(amount, interaction.user.id)),
             )
-            .await
-            .unwrap();
+            .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut recipient_row,
+                Event::Receive(ReceiveEvent::new(amount, interaction.user.id, recipient.id)),
+            )
+            .await?;
 
-        SendHandler::save(pool, row).await?;
+        SendHandler::save(&mut *tx, row).await?;
+        SendHandler::save(&mut *tx, recipient_row).await?;
+        tx.commit().await?;
 
         let embed = CreateEmbed::new().description(format!(
             "You sent {} <:coin:{COIN}> to {}\nStamina: {stamina}",
@@ -5,10 +5,10 @@ use sqlx::{Database, Pool, prelude::FromRow, types::Json};
 use zayden_core::{FormatNum, parse_options};
 
 use crate::{
-    Coins, Error, Gems, GoalsManager, ItemInventory, MaxBet, MaxValues, Prestige, Result,
-    SHOP_ITEMS, SUPER_USER, ShopCurrency, ShopItem, ShopPage,
+    Coins, Error, Gems, ItemInventory, LedgerManager, LedgerSource, MaxBet, MaxValues, Prestige,
+    Result, SHOP_ITEMS, SUPER_USER, ShopCurrency, ShopItem, ShopPage,
     commands::shop::ShopManager,
-    events::{Dispatch, Event, ShopPurchaseEvent},
+    events::{Dispatch, Event, ShopPurchaseEvent, Subscriber},
     models::{GamblingItem, Mining},
 };
 
@@ -184,7 +184,12 @@ impl MaxBet for BuyRow {
     }
 }
 
-pub async fn buy<Db: Database, GoalsHandler: GoalsManager<Db>, BuyHandler: ShopManager<Db>>(
+pub async fn buy<
+    Db: Database,
+    EventHandler: Subscriber<Db>,
+    BuyHandler: ShopManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+>(
     ctx: &Context,
     interaction: &CommandInteraction,
     pool: &Pool<Db>,
@@ -204,7 +209,8 @@ pub async fn buy<Db: Database, GoalsHandler: GoalsManager<Db>, BuyHandler: ShopM
         unreachable!("amount is required")
     };
 
-    let mut row = match BuyHandler::buy_row(pool, interaction.user.id).await? {
+    let mut tx = pool.begin().await?;
+    let mut row = match BuyHandler::buy_row(&mut *tx, interaction.user.id).await? {
         Some(row) => row,
         None => BuyRow::new(interaction.user.id),
     };
@@ -275,14 +281,39 @@ pub async fn buy<Db: Database, GoalsHandler: GoalsManager<Db>, BuyHandler: ShopM
         edit_inv(&mut row, item, amount)
     };
 
-    Dispatch::<Db, GoalsHandler>::new(pool)
+    for (cost, currency) in costs.iter().copied() {
+        match currency {
+            ShopCurrency::Coins => {
+                LedgerHandler::coins(
+                    &mut *tx,
+                    interaction.user.id,
+                    -cost,
+                    LedgerSource::Buy(item.id),
+                )
+                .await?
+            }
+            ShopCurrency::Gems => {
+                LedgerHandler::gems(
+                    &mut *tx,
+                    interaction.user.id,
+                    -cost,
+                    LedgerSource::Buy(item.id),
+                )
+                .await?
+            }
+            _ => {}
+        }
+    }
+
+    Dispatch::<Db, EventHandler>::new(&mut *tx)
         .fire(
             &mut row,
             Event::ShopPurchase(ShopPurchaseEvent::new(interaction.user.id, item.id)),
         )
         .await?;
 
-    BuyHandler::buy_save(pool, row).await.unwrap();
+    BuyHandler::buy_save(&mut *tx, row).await.unwrap();
+    tx.commit().await?;
 
     let cost = costs
         .into_iter()
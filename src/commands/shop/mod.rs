@@ -14,16 +14,19 @@ pub use buy::{BuyRow, buy};
 pub use list::{ListRow, list};
 pub use sell::{SellRow, sell};
 
-use crate::{GoalsManager, Result, SHOP_ITEMS, ShopPage};
+use crate::events::Subscriber;
+use crate::{LedgerManager, Result, SHOP_ITEMS, ShopPage};
 
 use super::Commands;
 
 #[async_trait]
 pub trait ShopManager<Db: Database> {
-    async fn buy_row(pool: &Pool<Db>, id: impl Into<UserId> + Send)
-    -> sqlx::Result<Option<BuyRow>>;
+    async fn buy_row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<BuyRow>>;
 
-    async fn buy_save(pool: &Pool<Db>, row: BuyRow) -> sqlx::Result<AnyQueryResult>;
+    async fn buy_save(conn: &mut Db::Connection, row: BuyRow) -> sqlx::Result<AnyQueryResult>;
 
     async fn list_row(
         pool: &Pool<Db>,
@@ -31,18 +34,19 @@ pub trait ShopManager<Db: Database> {
     ) -> sqlx::Result<Option<ListRow>>;
 
     async fn sell_row(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         id: impl Into<UserId> + Send,
     ) -> sqlx::Result<Option<SellRow>>;
 
-    async fn sell_save(pool: &Pool<Db>, row: SellRow) -> sqlx::Result<AnyQueryResult>;
+    async fn sell_save(conn: &mut Db::Connection, row: SellRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 impl Commands {
     pub async fn shop<
         Db: Database,
-        GoalsHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         ShopHandler: ShopManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -59,8 +63,19 @@ impl Commands {
 
         match command.name {
             "list" => list::<Db, ShopHandler>(ctx, interaction, pool).await?,
-            "buy" => buy::<Db, GoalsHandler, ShopHandler>(ctx, interaction, pool, options).await?,
-            "sell" => sell::<Db, ShopHandler>(ctx, interaction, pool, options).await?,
+            "buy" => {
+                buy::<Db, EventHandler, ShopHandler, LedgerHandler>(ctx, interaction, pool, options)
+                    .await?
+            }
+            "sell" => {
+                sell::<Db, EventHandler, ShopHandler, LedgerHandler>(
+                    ctx,
+                    interaction,
+                    pool,
+                    options,
+                )
+                .await?
+            }
             _ => unreachable!("Invalid subcommand name"),
         };
 
@@ -7,14 +7,20 @@ use sqlx::{Database, Pool};
 use zayden_core::{FormatNum, parse_options};
 
 use crate::commands::shop::ShopManager;
+use crate::events::{Dispatch, Event, SellEvent, Subscriber};
 use crate::models::{GamblingItem, ItemInventory};
 use crate::shop::SALES_TAX;
-use crate::{COIN, Coins, Error, Result, SHOP_ITEMS};
+use crate::{
+    COIN, Coins, Error, Gems, LedgerManager, LedgerSource, MaxBet, Prestige, Result, SHOP_ITEMS,
+};
 
 #[derive(FromRow)]
 pub struct SellRow {
     pub id: i64,
     pub coins: i64,
+    pub gems: i64,
+    pub level: Option<i32>,
+    pub prestige: i64,
     pub inventory: Option<Json<Vec<GamblingItem>>>,
 }
 
@@ -25,6 +31,9 @@ impl SellRow {
         Self {
             id: id.get() as i64,
             coins: 0,
+            gems: 0,
+            level: Some(0),
+            prestige: 0,
             inventory: Some(Json(Vec::new())),
         }
     }
@@ -40,6 +49,28 @@ impl Coins for SellRow {
     }
 }
 
+impl Gems for SellRow {
+    fn gems(&self) -> i64 {
+        self.gems
+    }
+
+    fn gems_mut(&mut self) -> &mut i64 {
+        &mut self.gems
+    }
+}
+
+impl Prestige for SellRow {
+    fn prestige(&self) -> i64 {
+        self.prestige
+    }
+}
+
+impl MaxBet for SellRow {
+    fn level(&self) -> i32 {
+        self.level.unwrap_or_default()
+    }
+}
+
 impl ItemInventory for SellRow {
     fn inventory(&self) -> &[GamblingItem] {
         match self.inventory.as_ref() {
@@ -53,7 +84,12 @@ impl ItemInventory for SellRow {
     }
 }
 
-pub async fn sell<Db: Database, Manager: ShopManager<Db>>(
+pub async fn sell<
+    Db: Database,
+    EventHandler: Subscriber<Db>,
+    Manager: ShopManager<Db>,
+    Ledger: LedgerManager<Db>,
+>(
     ctx: &Context,
     interaction: &CommandInteraction,
     pool: &Pool<Db>,
@@ -78,7 +114,11 @@ pub async fn sell<Db: Database, Manager: ShopManager<Db>>(
         .expect("Preset choices so item should always exist");
     let payment = ((item.coin_cost().unwrap() as f64) * (amount as f64) * (1.0 - SALES_TAX)) as i64;
 
-    let mut row = match Manager::sell_row(pool, interaction.user.id).await.unwrap() {
+    let mut tx = pool.begin().await?;
+    let mut row = match Manager::sell_row(&mut *tx, interaction.user.id)
+        .await
+        .unwrap()
+    {
         Some(row) => row,
         None => SellRow::new(interaction.user.id),
     };
@@ -99,7 +139,28 @@ pub async fn sell<Db: Database, Manager: ShopManager<Db>>(
     let quantity = row.edit_item_quantity(item.id, -amount).unwrap();
 
     *row.coins_mut() += payment;
-    Manager::sell_save(pool, row).await.unwrap();
+    Ledger::coins(
+        &mut *tx,
+        interaction.user.id,
+        payment,
+        LedgerSource::Sell(item.id),
+    )
+    .await?;
+
+    Dispatch::<Db, EventHandler>::new(&mut *tx)
+        .fire(
+            &mut row,
+            Event::Sell(SellEvent::new(
+                interaction.user.id,
+                item.id,
+                amount,
+                payment,
+            )),
+        )
+        .await?;
+
+    Manager::sell_save(&mut *tx, row).await.unwrap();
+    tx.commit().await?;
 
     interaction
         .edit_response(
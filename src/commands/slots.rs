@@ -0,0 +1,267 @@
+use std::fmt::Display;
+
+use serenity::all::{
+    Colour, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateEmbed, EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+use sqlx::{Database, Pool};
+use zayden_core::{FormatNum, parse_options};
+
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::utils::{Emoji, GameResult, signed};
+use crate::{
+    ActiveGames, COIN, Coins, EffectsManager, GameCache, GameManager, GameRow, LedgerManager,
+    LedgerSource, Result, VerifyBet,
+};
+
+use super::Commands;
+
+impl Commands {
+    pub async fn slots<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        EffectsHandler: EffectsManager<Db> + Send,
+        GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+    >(
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        options: Vec<ResolvedOption<'_>>,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        interaction.defer(ctx).await.unwrap();
+
+        let mut options = parse_options(options);
+
+        let Some(ResolvedValue::Integer(bet)) = options.remove("bet") else {
+            unreachable!("bet is required")
+        };
+
+        let mut tx = pool.begin().await?;
+        let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+            .await?
+            .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+        GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "slots").await?;
+        row.verify_bet(bet)?;
+        row.bet(bet);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            -bet,
+            LedgerSource::Game("slots"),
+        )
+        .await?;
+
+        let grid = spin();
+        let wins = line_wins(&grid);
+
+        let multiplier = wins.iter().map(|(_, pay)| pay.multiplier).sum::<i64>();
+        let payout = bet * multiplier / PAYLINES.len() as i64;
+        let winner = payout > bet;
+
+        let (payout, effects) =
+            EffectsHandler::payout(&mut *tx, interaction.user.id, bet, payout, winner).await;
+
+        row.add_coins(payout);
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            payout,
+            LedgerSource::Game("slots"),
+        )
+        .await?;
+
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
+            .fire(
+                &mut row,
+                Event::Game(
+                    GameEvent::new("slots", interaction.user.id, bet, winner)
+                        .set_payout(payout)
+                        .set_effects(effects),
+                ),
+            )
+            .await?;
+
+        let coins = row.coins();
+
+        GameHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
+        GameCache::update(ctx, interaction.user.id).await;
+
+        let (title, colour) = if winner {
+            ("🎰 Slots 🎰 - You Won!", Colour::DARK_GREEN)
+        } else {
+            ("🎰 Slots 🎰 - You Lost!", Colour::RED)
+        };
+
+        let reels = (0..ROWS)
+            .map(|r| {
+                grid.iter()
+                    .map(|reel| GameResult::from(reel[r]).emoji())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let lines = if wins.is_empty() {
+            String::from("No winning lines")
+        } else {
+            wins.iter()
+                .map(|(line, pay)| {
+                    format!(
+                        "Line {}: {} x{} ({}x)",
+                        line + 1,
+                        GameResult::from(pay.symbol).emoji(),
+                        pay.count,
+                        pay.multiplier
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let desc = format!(
+            "Your bet: {} <:coin:{COIN}>\n\n{reels}\n\n{lines}\n\nPayout: {} ({})\nYour coins: {}",
+            bet.format(),
+            payout.format(),
+            signed(payout - bet),
+            coins.format()
+        );
+
+        let embed = CreateEmbed::new()
+            .title(title)
+            .description(desc)
+            .colour(colour);
+
+        interaction
+            .edit_response(ctx, EditInteractionResponse::new().embed(embed))
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    pub fn register_slots() -> CreateCommand {
+        CreateCommand::new("slots")
+            .description("Spin the slot machine")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
+                    .required(true),
+            )
+    }
+}
+
+const ROWS: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Symbol {
+    Cherry,
+    Lemon,
+    Bell,
+    Clover,
+    Diamond,
+    Seven,
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cherry => write!(f, "Cherry"),
+            Self::Lemon => write!(f, "Lemon"),
+            Self::Bell => write!(f, "Bell"),
+            Self::Clover => write!(f, "Clover"),
+            Self::Diamond => write!(f, "Diamond"),
+            Self::Seven => write!(f, "Seven"),
+        }
+    }
+}
+
+impl From<Symbol> for GameResult<'_> {
+    fn from(value: Symbol) -> Self {
+        let emoji = match value {
+            Symbol::Cherry => Emoji::Str("🍒"),
+            Symbol::Lemon => Emoji::Str("🍋"),
+            Symbol::Bell => Emoji::Str("🔔"),
+            Symbol::Clover => Emoji::Str("🍀"),
+            Symbol::Diamond => Emoji::Str("💎"),
+            Symbol::Seven => Emoji::Str("7️⃣"),
+        };
+
+        Self {
+            name: value.to_string(),
+            emoji,
+        }
+    }
+}
+
+use Symbol::{Bell as B, Cherry as C, Clover as F, Diamond as D, Lemon as L, Seven as S};
+
+// Each strip holds 7 cherries, 5 lemons, 4 bells, 2 clovers, 1 diamond and 1 seven.
+const REELS: [[Symbol; 20]; 3] = [
+    [C, L, B, C, F, L, C, B, D, C, L, B, C, S, L, C, F, B, L, C],
+    [L, C, B, L, C, F, C, B, L, S, C, B, C, L, F, C, D, L, B, C],
+    [C, B, L, C, D, L, B, C, F, C, L, S, B, C, L, C, B, F, L, C],
+];
+
+// Row index shown on each reel: the three rows and both diagonals.
+const PAYLINES: [[usize; 3]; 5] = [[0, 0, 0], [1, 1, 1], [2, 2, 2], [0, 1, 2], [2, 1, 0]];
+
+struct Pay {
+    symbol: Symbol,
+    count: usize,
+    multiplier: i64,
+}
+
+impl Pay {
+    const fn new(symbol: Symbol, count: usize, multiplier: i64) -> Self {
+        Self {
+            symbol,
+            count,
+            multiplier,
+        }
+    }
+}
+
+// Multipliers are per line, where each line carries a fifth of the bet.
+// Highest counts come first so the best matching pay is found first.
+static PAYTABLE: [Pay; 7] = [
+    Pay::new(Symbol::Seven, 3, 1000),
+    Pay::new(Symbol::Diamond, 3, 500),
+    Pay::new(Symbol::Clover, 3, 100),
+    Pay::new(Symbol::Bell, 3, 20),
+    Pay::new(Symbol::Lemon, 3, 10),
+    Pay::new(Symbol::Cherry, 3, 4),
+    Pay::new(Symbol::Cherry, 2, 2),
+];
+
+fn spin() -> [[Symbol; ROWS]; 3] {
+    REELS.map(|strip| {
+        let stop = rand::random_range(0..strip.len());
+        std::array::from_fn(|r| strip[(stop + r) % strip.len()])
+    })
+}
+
+fn line_wins(grid: &[[Symbol; ROWS]; 3]) -> Vec<(usize, &'static Pay)> {
+    PAYLINES
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let symbols = line
+                .iter()
+                .zip(grid.iter())
+                .map(|(row, reel)| reel[*row])
+                .collect::<Vec<_>>();
+
+            let first = symbols[0];
+            let run = symbols.iter().take_while(|s| **s == first).count();
+
+            PAYTABLE
+                .iter()
+                .find(|pay| pay.symbol == first && pay.count <= run)
+                .map(|pay| (i, pay))
+        })
+        .collect()
+}
@@ -3,31 +3,33 @@ use std::{marker::PhantomData, time::Duration};
 use futures::StreamExt;
 use rand::{rng, seq::IndexedRandom};
 use serenity::all::{
-    ActionRow, ActionRowComponent, ButtonStyle, Colour, CommandInteraction, CommandOptionType,
-    ComponentInteraction, Context, CreateActionRow, CreateButton, CreateCommand,
-    CreateCommandOption, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
-    EditInteractionResponse, Mentionable, ReactionType, ResolvedOption, ResolvedValue, UserId,
+    ButtonStyle, Colour, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    Mentionable, Message, ResolvedOption, ResolvedValue, UserId,
 };
-use sqlx::{Database, Pool};
-use zayden_core::parse_options;
+use sqlx::{Database, Pool, Transaction};
+use zayden_core::{FormatNum, parse_options};
 
 use crate::{
-    BLANK, COIN, Coins, EffectsManager, GameCache, GameManager, GameRow, GoalsManager, Result,
-    VerifyBet,
-    events::{Dispatch, Event, GameEvent},
+    ActiveGames, BLANK, COIN, Coins, EffectsManager, Error, GameCache, GameLock, GameManager,
+    GameRow, LedgerManager, LedgerSource, Result, SessionManager, SessionRow, VerifyBet,
+    duel::ephemeral,
+    events::{Dispatch, Event, GameEvent, Subscriber},
+    tictactoe::{Board, Difficulty, Mark, best_move},
+    utils::signed,
 };
 
 use super::Commands;
 
-const EMOJI_P1: char = '❌';
-const EMOJI_P2: char = '⭕';
-
 impl Commands {
     pub async fn tictactoe<
         Db: Database,
-        GoalHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         EffectsHandler: EffectsManager<Db> + Send,
         GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -36,12 +38,14 @@ impl Commands {
     ) -> Result<()> {
         interaction.defer(ctx).await.unwrap();
 
-        let row = GameHandler::row(pool, interaction.user.id)
+        let mut tx = pool.begin().await?;
+        let row = GameHandler::row(&mut *tx, interaction.user.id)
             .await
             .unwrap()
             .unwrap_or_else(|| GameRow::new(interaction.user.id));
 
         GameCache::can_play(ctx, interaction.user.id).await?;
+        let _lock = ActiveGames::lock(ctx, interaction.user.id, "tictactoe").await?;
 
         let mut options = parse_options(options);
 
@@ -53,14 +57,46 @@ impl Commands {
             unreachable!("bet is required option")
         };
 
+        let size = size.parse::<usize>().unwrap();
+
+        let win_length = match options.remove("win_length") {
+            Some(ResolvedValue::Integer(win_length)) => win_length as usize,
+            _ => size,
+        };
+
+        let board = Board::new(size, win_length);
+
+        if let Some(ResolvedValue::String(difficulty)) = options.remove("difficulty") {
+            return vs_bot::<
+                Db,
+                EventHandler,
+                EffectsHandler,
+                GameHandler,
+                LedgerHandler,
+                SessionHandler,
+            >(
+                ctx,
+                interaction,
+                pool,
+                tx,
+                row,
+                board,
+                bet,
+                difficulty.parse().unwrap(),
+            )
+            .await;
+        }
+
         row.verify_bet(bet)?;
 
-        GameHandler::save(pool, row).await.unwrap();
+        GameHandler::save(&mut *tx, row).await.unwrap();
+        tx.commit().await?;
         GameCache::update(ctx, interaction.user.id).await;
 
         let embed = CreateEmbed::new().title("TicTacToe").description(format!(
-            "{} wants to play tic-tac-toe ({size}x{size}) for **{bet}** <:coin:{COIN}>",
+            "{} wants to play tic-tac-toe ({size}x{size}, {} in a row) for **{bet}** <:coin:{COIN}>",
             interaction.user.mention(),
+            board.win_length()
         ));
 
         let msg = interaction
@@ -84,22 +120,36 @@ impl Commands {
             .await
             .unwrap();
 
+        SessionHandler::save(
+            &mut *pool.acquire().await?,
+            SessionRow::new("tictactoe", &msg),
+        )
+        .await?;
+
         let mut stream = msg
             .await_component_interactions(ctx)
             .timeout(Duration::from_secs(120))
             .stream();
 
-        let mut state =
-            GameState::<Db, GameHandler>::new(interaction.user.id, size.parse().unwrap(), bet);
+        let mut state = GameState::<Db, GameHandler>::new(interaction.user.id, board, bet);
 
         while let Some(component) = stream.next().await {
-            if !run_component(ctx, interaction, component, pool, &mut state).await? {
+            if !run_component::<Db, GameHandler, LedgerHandler, SessionHandler>(
+                ctx,
+                interaction,
+                component,
+                pool,
+                &mut state,
+            )
+            .await?
+            {
                 break;
             }
         }
 
-        let mut p1_row = state.p1_row(pool).await;
-        let mut p2_row = state.p2_row(pool).await;
+        let mut tx = pool.begin().await?;
+        let mut p1_row = state.p1_row(&mut *tx).await;
+        let mut p2_row = state.p2_row(&mut *tx).await;
 
         let [p1, p2] = state.players;
 
@@ -125,32 +175,38 @@ impl Commands {
                 .description("It's a draw!")
                 .colour(Colour::ORANGE)
         } else {
-            p1_row.add_coins(bet);
-
             CreateEmbed::new()
                 .title("TicTacToe")
                 .description("This game timed out after 2 minutes of inactivity")
                 .colour(Colour::TEAL)
         };
 
-        let dispatch = Dispatch::<Db, GoalHandler>::new(pool);
-
-        dispatch
-            .fire(
-                &mut p1_row,
-                Event::Game(GameEvent::new("rps", p1, state.bet, false)), // TODO: Fix win logic
-            )
-            .await?;
-
-        dispatch
-            .fire(
-                &mut p2_row,
-                Event::Game(GameEvent::new("rps", p2, state.bet, false)), // TODO: Fix win logic
-            )
-            .await?;
+        // Nothing was staked if nobody accepted the challenge
+        if p1 != p2 {
+            for (row, id) in [(&mut p1_row, p1), (&mut p2_row, p2)] {
+                let event = match state.winner {
+                    Some(winner) => GameEvent::new("tictactoe", id, state.bet, winner == id),
+                    None => GameEvent::new_draw("tictactoe", id, state.bet),
+                };
+
+                let payout = match state.winner {
+                    Some(winner) if winner == id => bet * 2,
+                    Some(_) => 0,
+                    None => bet,
+                };
+
+                LedgerHandler::coins(&mut *tx, id, payout, LedgerSource::Game("tictactoe")).await?;
+
+                Dispatch::<Db, EventHandler>::new(&mut *tx)
+                    .fire(row, Event::Game(event.set_payout(payout)))
+                    .await?;
+            }
+        }
 
-        GameHandler::save(pool, p1_row).await?;
-        GameHandler::save(pool, p2_row).await?;
+        SessionHandler::delete(&mut *tx, msg.id).await?;
+        GameHandler::save(&mut *tx, p1_row).await?;
+        GameHandler::save(&mut *tx, p2_row).await?;
+        tx.commit().await?;
 
         GameCache::update(ctx, p1).await;
         GameCache::update(ctx, p2).await;
@@ -186,15 +242,232 @@ impl Commands {
                 CreateCommandOption::new(CommandOptionType::Integer, "bet", "The amount to bet.")
                     .required(true),
             )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "win_length",
+                    "How many in a row are needed to win. Defaults to the board size.",
+                )
+                .min_int_value(3)
+                .max_int_value(5),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "difficulty",
+                    "Play against Zayden instead of another player",
+                )
+                .add_string_choice("Easy", "easy")
+                .add_string_choice("Hard", "hard"),
+            )
+    }
+}
+
+async fn vs_bot<
+    Db: Database,
+    EventHandler: Subscriber<Db>,
+    EffectsHandler: EffectsManager<Db> + Send,
+    GameHandler: GameManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    pool: &Pool<Db>,
+    mut tx: Transaction<'_, Db>,
+    mut row: GameRow,
+    mut board: Board,
+    bet: i64,
+    difficulty: Difficulty,
+) -> Result<()> {
+    row.verify_bet(bet)?;
+    row.bet(bet);
+    LedgerHandler::coins(
+        &mut *tx,
+        interaction.user.id,
+        -bet,
+        LedgerSource::Game("tictactoe"),
+    )
+    .await?;
+
+    GameHandler::save(&mut *tx, row).await?;
+    tx.commit().await?;
+
+    // The player is always X, Zayden takes the second seat as O
+    if rand::random_bool(0.5) {
+        bot_turn(&mut board, difficulty).await;
     }
+
+    let embed = CreateEmbed::new()
+        .title("TicTacToe")
+        .description(format!("{}'s Turn", interaction.user.mention()));
+
+    let msg = interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed.clone())
+                .components(board_components(&board, false)),
+        )
+        .await?;
+
+    SessionHandler::save(
+        &mut *pool.acquire().await?,
+        SessionRow::new("tictactoe", &msg).stake(interaction.user.id, bet),
+    )
+    .await?;
+
+    let mut stream = msg
+        .await_component_interactions(ctx)
+        .author_id(interaction.user.id)
+        .timeout(Duration::from_secs(120))
+        .stream();
+
+    while let Some(component) = stream.next().await {
+        let (row, col) = parse_cell(&component.data.custom_id);
+
+        if board.place(row, col, Mark::X) && !board.is_finished() {
+            bot_turn(&mut board, difficulty).await;
+        }
+
+        if board.is_finished() {
+            component
+                .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                .await?;
+
+            break;
+        }
+
+        let msg = CreateInteractionResponseMessage::new()
+            .embed(embed.clone())
+            .components(board_components(&board, false));
+
+        component
+            .create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
+            .await?;
+    }
+
+    let (result, win, payout, colour) = match board.winner() {
+        Some(Mark::X) => ("You Won!", true, bet * 2, Colour::DARK_GREEN),
+        Some(Mark::O) => ("Zayden Won!", false, 0, Colour::RED),
+        None if board.is_full() => ("Draw!", false, bet, Colour::ORANGE),
+        None => ("Timed Out!", false, 0, Colour::RED),
+    };
+
+    let mut tx = pool.begin().await?;
+    let mut row = GameHandler::row(&mut *tx, interaction.user.id)
+        .await?
+        .unwrap_or_else(|| GameRow::new(interaction.user.id));
+
+    let (payout, effects) =
+        EffectsHandler::payout(&mut *tx, interaction.user.id, bet, payout, win).await;
+
+    let event = if board.is_full() && board.winner().is_none() {
+        GameEvent::new_draw("tictactoe", interaction.user.id, bet)
+    } else {
+        GameEvent::new("tictactoe", interaction.user.id, bet, win)
+    };
+
+    row.add_coins(payout);
+    LedgerHandler::coins(
+        &mut *tx,
+        interaction.user.id,
+        payout,
+        LedgerSource::Game("tictactoe"),
+    )
+    .await?;
+
+    Dispatch::<Db, EventHandler>::new(&mut *tx)
+        .fire(
+            &mut row,
+            Event::Game(event.set_payout(payout).set_effects(effects)),
+        )
+        .await?;
+
+    let coins = row.coins();
+
+    SessionHandler::delete(&mut *tx, msg.id).await?;
+    GameHandler::save(&mut *tx, row).await?;
+    tx.commit().await?;
+    GameCache::update(ctx, interaction.user.id).await;
+
+    let embed = CreateEmbed::new()
+        .title(format!("TicTacToe - {result}"))
+        .description(format!(
+            "Your bet: {} <:coin:{COIN}>\n\nPayout: {} ({})\nYour coins: {}",
+            bet.format(),
+            payout.format(),
+            signed(payout - bet),
+            coins.format()
+        ))
+        .colour(colour);
+
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(board_components(&board, true)),
+        )
+        .await
+        .unwrap();
+
+    Ok(())
+}
+
+async fn bot_turn(board: &mut Board, difficulty: Difficulty) {
+    let snapshot = board.clone();
+
+    // The hard search runs for a while on the larger boards, so it is kept off
+    // the async workers
+    let best = tokio::task::spawn_blocking(move || best_move(&snapshot, Mark::O, difficulty))
+        .await
+        .unwrap();
+
+    if let Some((row, col)) = best {
+        board.place(row, col, Mark::O);
+    }
+}
+
+fn parse_cell(custom_id: &str) -> (usize, usize) {
+    let mut pos = custom_id.strip_prefix("ttt_").unwrap().chars();
+    let row = pos.next().unwrap().to_digit(10).unwrap() as usize;
+    let col = pos.next().unwrap().to_digit(10).unwrap() as usize;
+
+    (row, col)
+}
+
+fn board_components(board: &Board, disabled: bool) -> Vec<CreateActionRow> {
+    let size = board.size();
+
+    (0..size)
+        .map(|row| {
+            let buttons = (0..size)
+                .map(|col| {
+                    let button = CreateButton::new(format!("ttt_{row}{col}"))
+                        .style(ButtonStyle::Secondary)
+                        .disabled(disabled);
+
+                    match board.get(row, col) {
+                        Some(mark) => button.emoji(mark.emoji()),
+                        None => button.emoji(BLANK),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            CreateActionRow::Buttons(buttons)
+        })
+        .collect()
 }
 
 struct GameState<Db: Database, Manager: GameManager<Db>> {
-    size: usize,
+    board: Board,
     players: [UserId; 2],
     current_turn: UserId,
     bet: i64,
     winner: Option<UserId>,
+    // The opponent is kept out of other games once they accept
+    p2_lock: Option<GameLock>,
 
     _db: PhantomData<Db>,
     _manager: PhantomData<Manager>,
@@ -205,34 +478,35 @@ where
     Db: Database,
     Manager: GameManager<Db>,
 {
-    fn new(p1: impl Into<UserId>, size: usize, bet: i64) -> Self {
+    fn new(p1: impl Into<UserId>, board: Board, bet: i64) -> Self {
         let p1 = p1.into();
 
         Self {
-            size,
+            board,
             players: [p1, p1],
             current_turn: p1,
             bet,
             winner: None,
+            p2_lock: None,
 
             _db: PhantomData,
             _manager: PhantomData,
         }
     }
 
-    async fn p1_row(&self, pool: &Pool<Db>) -> GameRow {
+    async fn p1_row(&self, conn: &mut Db::Connection) -> GameRow {
         let id = self.players[0];
 
-        Manager::row(pool, id)
+        Manager::row(conn, id)
             .await
             .unwrap()
             .unwrap_or_else(|| GameRow::new(id))
     }
 
-    async fn p2_row(&self, pool: &Pool<Db>) -> GameRow {
+    async fn p2_row(&self, conn: &mut Db::Connection) -> GameRow {
         let id = self.players[1];
 
-        Manager::row(pool, id)
+        Manager::row(conn, id)
             .await
             .unwrap()
             .unwrap_or_else(|| GameRow::new(id))
@@ -242,9 +516,27 @@ where
         p1.verify_bet(self.bet)?;
         p2.verify_bet(self.bet)
     }
+
+    // The first player is always X
+    fn current_mark(&self) -> Mark {
+        if self.current_turn == self.players[0] {
+            Mark::X
+        } else {
+            Mark::O
+        }
+    }
+
+    fn components(&self) -> Vec<CreateActionRow> {
+        board_components(&self.board, false)
+    }
 }
 
-async fn run_component<Db: Database, Manager: GameManager<Db>>(
+async fn run_component<
+    Db: Database,
+    Manager: GameManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
     ctx: &Context,
     interaction: &CommandInteraction,
     component: ComponentInteraction,
@@ -275,7 +567,28 @@ async fn run_component<Db: Database, Manager: GameManager<Db>>(
     }
 
     if custom_id == "ttt_accept" {
-        let msg = accept(pool, state, component.user.id).await?;
+        if state.p2_lock.is_some() {
+            ephemeral(ctx, &component, "This game has already started.").await?;
+            return Ok(true);
+        }
+
+        let msg = match accept::<Db, Manager, LedgerHandler, SessionHandler>(
+            ctx,
+            pool,
+            state,
+            component.user.id,
+            &component.message,
+        )
+        .await
+        {
+            Ok(msg) => msg,
+            Err(e @ (Error::Serenity(_) | Error::Sqlx(_))) => return Err(e),
+            // The challenge stays open for someone who can cover the bet
+            Err(e) => {
+                ephemeral(ctx, &component, &e.to_string()).await?;
+                return Ok(true);
+            }
+        };
 
         component
             .create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
@@ -289,60 +602,19 @@ async fn run_component<Db: Database, Manager: GameManager<Db>>(
         return Ok(true);
     }
 
-    let mut pos = custom_id.strip_prefix("ttt_").unwrap().chars();
-    let i = pos.next().unwrap().to_digit(10).unwrap() as usize;
-    let j = pos.next().unwrap().to_digit(10).unwrap() as usize;
-
-    let mut components = component.message.components.clone();
-
-    let ActionRowComponent::Button(button) = components
-        .get_mut(i)
-        .unwrap()
-        .components
-        .get_mut(j)
-        .unwrap()
-    else {
-        unreachable!("Component must be a button")
-    };
+    let (row, col) = parse_cell(custom_id);
 
-    if button.emoji == Some(EMOJI_P1.into()) || button.emoji == Some(EMOJI_P2.into()) {
+    if !state.board.place(row, col, state.current_mark()) {
         return Ok(true);
     }
 
-    let emoji = if state.current_turn == state.players[0] {
-        ReactionType::from(EMOJI_P1)
-    } else {
-        ReactionType::from(EMOJI_P2)
-    };
-
-    button.emoji = Some(emoji.clone());
-
-    if check_win(state, &components, emoji) {
+    if state.board.winner().is_some() {
         state.winner = Some(state.current_turn);
         return Ok(false);
-    } else if check_draw(&components) {
+    } else if state.board.is_full() {
         return Ok(false);
     }
 
-    let components = components
-        .into_iter()
-        .map(|row| {
-            let buttons = row
-                .components
-                .into_iter()
-                .map(|c| {
-                    let ActionRowComponent::Button(b) = c else {
-                        unreachable!("Component must be of type Button")
-                    };
-
-                    b.into()
-                })
-                .collect::<Vec<CreateButton>>();
-
-            CreateActionRow::Buttons(buttons)
-        })
-        .collect::<Vec<CreateActionRow>>();
-
     // Next player
     state.current_turn = if state.current_turn == state.players[0] {
         state.players[1]
@@ -356,7 +628,7 @@ async fn run_component<Db: Database, Manager: GameManager<Db>>(
 
     let msg = CreateInteractionResponseMessage::new()
         .embed(embed)
-        .components(components);
+        .components(state.components());
 
     component
         .create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
@@ -366,115 +638,57 @@ async fn run_component<Db: Database, Manager: GameManager<Db>>(
     Ok(true)
 }
 
-async fn accept<Db: Database, Manager: GameManager<Db>>(
+async fn accept<
+    Db: Database,
+    Manager: GameManager<Db>,
+    LedgerHandler: LedgerManager<Db>,
+    SessionHandler: SessionManager<Db>,
+>(
+    ctx: &Context,
     pool: &Pool<Db>,
     state: &mut GameState<Db, Manager>,
     p2: UserId,
+    msg: &Message,
 ) -> Result<CreateInteractionResponseMessage> {
-    state.players[1] = p2;
+    GameCache::can_play(ctx, p2).await?;
 
-    let mut p1_row = state.p1_row(pool).await;
-    let mut p2_row = state.p2_row(pool).await;
+    let mut tx = pool.begin().await?;
+    let mut p1_row = state.p1_row(&mut *tx).await;
+    let mut p2_row = Manager::row(&mut *tx, p2)
+        .await?
+        .unwrap_or_else(|| GameRow::new(p2));
 
     state.verify_bet(&p1_row, &p2_row)?;
 
-    state.current_turn = *state.players.choose(&mut rng()).unwrap();
+    let lock = ActiveGames::lock(ctx, p2, "tictactoe").await?;
+    let players = [state.players[0], p2];
 
     p1_row.add_coins(-state.bet);
     p2_row.add_coins(-state.bet);
 
-    Manager::save(pool, p1_row).await.unwrap();
-    Manager::save(pool, p2_row).await.unwrap();
+    for id in players {
+        LedgerHandler::coins(&mut *tx, id, -state.bet, LedgerSource::Game("tictactoe")).await?;
+    }
+
+    // Replaces the session saved for the challenge, which held no stakes
+    let session = SessionRow::new("tictactoe", msg)
+        .stake(players[0], state.bet)
+        .stake(players[1], state.bet);
+
+    Manager::save(&mut *tx, p1_row).await?;
+    Manager::save(&mut *tx, p2_row).await?;
+    SessionHandler::save(&mut *tx, session).await?;
+    tx.commit().await?;
+
+    state.players = players;
+    state.current_turn = *players.choose(&mut rng()).unwrap();
+    state.p2_lock = Some(lock);
 
     let embed = CreateEmbed::new()
         .title("TicTacToe")
         .description(format!("{}'s Turn", state.current_turn.mention()));
 
-    let components = (0..state.size)
-        .map(|i| {
-            let row = (0..state.size)
-                .map(|j| {
-                    CreateButton::new(format!("ttt_{}{}", i, j))
-                        .emoji(BLANK)
-                        .style(ButtonStyle::Secondary)
-                })
-                .collect::<Vec<_>>();
-
-            CreateActionRow::Buttons(row)
-        })
-        .collect::<Vec<_>>();
-
     Ok(CreateInteractionResponseMessage::new()
         .embed(embed)
-        .components(components))
-}
-
-fn check_win<Db: Database, Manager: GameManager<Db>>(
-    state: &GameState<Db, Manager>,
-    components: &[ActionRow],
-    target: ReactionType,
-) -> bool {
-    let get_emoji = |r: usize, c: usize| -> Option<&ReactionType> {
-        match components.get(r).unwrap().components.get(c).unwrap() {
-            ActionRowComponent::Button(b) => b.emoji.as_ref(),
-            _ => unreachable!("Component must be a button"),
-        }
-    };
-
-    let target = Some(target);
-
-    // Check rows
-    for r in 0..3 {
-        if (0..state.size)
-            .map(|c| get_emoji(r, c))
-            .all(|emoji| emoji == target.as_ref())
-        {
-            return true;
-        }
-    }
-
-    // Check columns
-    for c in 0..3 {
-        if (0..state.size)
-            .map(|r| get_emoji(r, c))
-            .all(|emoji| emoji == target.as_ref())
-        {
-            return true;
-        }
-    }
-
-    // Check diagonals
-    if (0..state.size)
-        .map(|i| get_emoji(i, i))
-        .all(|emoji| emoji == target.as_ref())
-    {
-        return true;
-    }
-
-    if (0..state.size)
-        .map(|row| get_emoji(row, state.size - 1 - row)) // Get element at (row, n-1-row)
-        .all(|emoji| emoji == target.as_ref())
-    {
-        return true;
-    }
-
-    // No win condition met
-    false
-}
-
-fn check_draw(components: &[ActionRow]) -> bool {
-    let x_emoji = Some(ReactionType::from(EMOJI_P1));
-    let o_emoji = Some(ReactionType::from(EMOJI_P2));
-
-    components
-        .iter()
-        .flat_map(|row| row.components.iter())
-        .filter_map(|component| {
-            if let ActionRowComponent::Button(button) = component {
-                Some(button)
-            } else {
-                None
-            }
-        })
-        .all(|button| button.emoji == x_emoji || button.emoji == o_emoji)
+        .components(state.components()))
 }
@@ -9,10 +9,11 @@ use sqlx::prelude::FromRow;
 use sqlx::{Database, Pool};
 use zayden_core::FormatNum;
 
-use crate::events::{Dispatch, Event};
+use crate::events::{Dispatch, Event, Subscriber};
 use crate::models::MineAmount;
 use crate::{
-    COIN, Coins, Gems, GoalsManager, MaxBet, MineHourly, Prestige, Result, Stamina, StaminaManager,
+    COIN, Coins, Gems, LedgerManager, LedgerSource, MaxBet, MineHourly, Prestige, Result, Stamina,
+    StaminaManager,
 };
 
 use super::Commands;
@@ -102,17 +103,21 @@ impl Prestige for WorkRow {
 
 #[async_trait]
 pub trait WorkManager<Db: Database> {
-    async fn row(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<WorkRow>>;
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<WorkRow>>;
 
-    async fn save(pool: &Pool<Db>, row: WorkRow) -> sqlx::Result<AnyQueryResult>;
+    async fn save(conn: &mut Db::Connection, row: WorkRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 impl Commands {
     pub async fn work<
         Db: Database,
         StaminaHandler: StaminaManager<Db>,
-        GoalHandler: GoalsManager<Db>,
+        EventHandler: Subscriber<Db>,
         WorkHandler: WorkManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
     >(
         ctx: &Context,
         interaction: &CommandInteraction,
@@ -120,7 +125,9 @@ impl Commands {
     ) -> Result<()> {
         interaction.defer(ctx).await?;
 
-        let mut row = match WorkHandler::row(pool, interaction.user.id).await.unwrap() {
+        let mut tx = pool.begin().await?;
+
+        let mut row = match WorkHandler::row(&mut *tx, interaction.user.id).await? {
             Some(row) => row,
             None => WorkRow::new(interaction.user.id),
         };
@@ -133,7 +140,8 @@ impl Commands {
 
         *row.coins_mut() += total_amount;
 
-        let gem_desc = if rand::random_bool(1.0 / 200.0) {
+        let gem_found = rand::random_bool(1.0 / 200.0);
+        let gem_desc = if gem_found {
             row.add_gems(1);
             "\n💎 You found a GEM!"
         } else {
@@ -142,7 +150,7 @@ impl Commands {
 
         let coins = row.coins_str();
 
-        Dispatch::<Db, GoalHandler>::new(pool)
+        Dispatch::<Db, EventHandler>::new(&mut *tx)
             .fire(&mut row, Event::Work(interaction.user.id))
             .await?;
 
@@ -151,7 +159,23 @@ impl Commands {
 
         let stamina = row.stamina_str();
 
-        WorkHandler::save(pool, row).await.unwrap();
+        LedgerHandler::coins(
+            &mut *tx,
+            interaction.user.id,
+            total_amount,
+            LedgerSource::Work,
+        )
+        .await?;
+        LedgerHandler::gems(
+            &mut *tx,
+            interaction.user.id,
+            gem_found as i64,
+            LedgerSource::Work,
+        )
+        .await?;
+
+        WorkHandler::save(&mut *tx, row).await?;
+        tx.commit().await?;
 
         let embed = CreateEmbed::new()
             .description(format!(
@@ -0,0 +1,36 @@
+use serenity::all::Context;
+use serenity::prelude::TypeMapKey;
+
+/// House settings the bot can override by inserting its own config into the
+/// context data. The defaults are used otherwise.
+#[derive(Clone, Copy)]
+pub struct GamblingConfig {
+    /// Percentage of the pot kept by the house in coinflip, dice and rock
+    /// paper scissors duels.
+    pub duel_rake: i64,
+    /// Gems charged for the daily goal reroll, free when 0.
+    pub goal_reroll_cost: i64,
+    /// Share of every crash bet the house keeps on average.
+    pub crash_house_edge: f64,
+}
+
+impl GamblingConfig {
+    pub async fn get(ctx: &Context) -> Self {
+        let data = ctx.data.read().await;
+        data.get::<GamblingConfig>().copied().unwrap_or_default()
+    }
+}
+
+impl Default for GamblingConfig {
+    fn default() -> Self {
+        Self {
+            duel_rake: 0,
+            goal_reroll_cost: 0,
+            crash_house_edge: 0.04,
+        }
+    }
+}
+
+impl TypeMapKey for GamblingConfig {
+    type Value = GamblingConfig;
+}
@@ -0,0 +1,351 @@
+use std::time::Duration;
+
+use futures::StreamExt;
+use serenity::all::{
+    ButtonStyle, Colour, CommandInteraction, ComponentInteraction, Context, CreateButton,
+    CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse, Mentionable, Message, MessageId, UserId,
+};
+use sqlx::{Database, Pool};
+use zayden_core::FormatNum;
+
+use crate::events::{Dispatch, Event, GameEvent, Subscriber};
+use crate::{
+    ActiveGames, COIN, Coins, Error, GameCache, GameLock, GameManager, GameRow, LedgerManager,
+    LedgerSource, Result, SessionManager, SessionRow, VerifyBet,
+};
+
+pub struct Duel {
+    pub game_id: &'static str,
+    pub title: &'static str,
+    pub challenger: UserId,
+    pub opponent: UserId,
+    pub bet: i64,
+    rake: i64,
+    // The challenge message, once both stakes are held for it
+    message: Option<MessageId>,
+    // Both players are kept out of other games until the duel is dropped
+    locks: Vec<GameLock>,
+}
+
+impl Duel {
+    pub fn new(
+        game_id: &'static str,
+        title: &'static str,
+        challenger: impl Into<UserId>,
+        opponent: impl Into<UserId>,
+        bet: i64,
+    ) -> Self {
+        Self {
+            game_id,
+            title,
+            challenger: challenger.into(),
+            opponent: opponent.into(),
+            bet,
+            rake: 0,
+            message: None,
+            locks: Vec::new(),
+        }
+    }
+
+    /// Percentage of the pot kept by the house, none unless set.
+    pub fn rake(mut self, percent: i64) -> Self {
+        self.rake = percent;
+        self
+    }
+
+    pub fn pot(&self) -> i64 {
+        self.bet * 2
+    }
+
+    pub fn winnings(&self) -> i64 {
+        self.pot() - self.pot() * self.rake / 100
+    }
+
+    pub fn players(&self) -> [UserId; 2] {
+        [self.challenger, self.opponent]
+    }
+
+    // Rows are always locked in ascending id order, so crossed challenges
+    // between the same two players can't deadlock
+    fn lock_order(&self) -> [UserId; 2] {
+        let mut players = self.players();
+        players.sort_unstable();
+        players
+    }
+
+    /// Posts the challenge and waits for the opponent to accept it.
+    /// Both stakes are taken once accepted. Returns false if the challenge
+    /// was declined, cancelled or expired, in which case nothing was taken.
+    /// The stakes are recorded as an open session until the duel is settled,
+    /// so they can be refunded if the bot restarts in between.
+    pub async fn challenge<
+        Db: Database,
+        Manager: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        &mut self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        pool: &Pool<Db>,
+        terms: &str,
+    ) -> Result<bool> {
+        if self.challenger == self.opponent {
+            return Err(Error::SelfChallenge);
+        }
+
+        // Only checked here, the stake is taken once the challenge is accepted
+        let row = Manager::row(&mut *pool.acquire().await?, self.challenger)
+            .await?
+            .unwrap_or_else(|| GameRow::new(self.challenger));
+
+        GameCache::can_play(ctx, self.challenger).await?;
+        row.verify_bet(self.bet)?;
+
+        let lock = ActiveGames::lock(ctx, self.challenger, self.game_id).await?;
+        self.locks.push(lock);
+
+        let mut desc = format!(
+            "{} challenges {} to {terms} for **{}** <:coin:{COIN}>\n\nWinner takes {} <:coin:{COIN}>",
+            self.challenger.mention(),
+            self.opponent.mention(),
+            self.bet.format(),
+            self.winnings().format()
+        );
+
+        if self.rake > 0 {
+            desc.push_str(&format!(" ({}% rake)", self.rake));
+        }
+
+        let msg = interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(self.embed(desc).colour(Colour::TEAL))
+                    .button(
+                        CreateButton::new("duel_accept")
+                            .label("Accept")
+                            .emoji('✅')
+                            .style(ButtonStyle::Secondary),
+                    )
+                    .button(
+                        CreateButton::new("duel_cancel")
+                            .label("Cancel")
+                            .emoji('❌')
+                            .style(ButtonStyle::Secondary),
+                    ),
+            )
+            .await?;
+
+        let mut stream = msg
+            .await_component_interactions(ctx)
+            .timeout(Duration::from_secs(120))
+            .stream();
+
+        while let Some(component) = stream.next().await {
+            let user_id = component.user.id;
+
+            if !self.players().contains(&user_id) {
+                ephemeral(ctx, &component, "This challenge isn't for you.").await?;
+                continue;
+            }
+
+            if component.data.custom_id == "duel_cancel" {
+                let desc = if user_id == self.challenger {
+                    String::from("Challenge cancelled")
+                } else {
+                    format!("{} declined the challenge", user_id.mention())
+                };
+
+                component
+                    .create_response(
+                        ctx,
+                        CreateInteractionResponse::UpdateMessage(
+                            CreateInteractionResponseMessage::new()
+                                .embed(self.embed(desc))
+                                .components(Vec::new()),
+                        ),
+                    )
+                    .await?;
+
+                return Ok(false);
+            }
+
+            if user_id == self.challenger {
+                ephemeral(ctx, &component, "Waiting for your opponent to accept.").await?;
+                continue;
+            }
+
+            match self
+                .escrow::<Db, Manager, LedgerHandler, SessionHandler>(ctx, pool, &msg)
+                .await
+            {
+                Ok(()) => {}
+                Err(e @ (Error::Serenity(_) | Error::Sqlx(_))) => return Err(e),
+                // Failed bet, cooldown or lock checks are shown to the opponent only
+                Err(e) => {
+                    ephemeral(ctx, &component, &e.to_string()).await?;
+                    continue;
+                }
+            }
+
+            component
+                .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                .await?;
+
+            return Ok(true);
+        }
+
+        interaction
+            .edit_response(
+                ctx,
+                EditInteractionResponse::new()
+                    .embed(self.embed("This challenge expired after 2 minutes"))
+                    .components(Vec::new()),
+            )
+            .await?;
+
+        Ok(false)
+    }
+
+    async fn escrow<
+        Db: Database,
+        Manager: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        &mut self,
+        ctx: &Context,
+        pool: &Pool<Db>,
+        msg: &Message,
+    ) -> Result<()> {
+        GameCache::can_play(ctx, self.opponent).await?;
+
+        let mut tx = pool.begin().await?;
+        let mut rows = Vec::with_capacity(2);
+        for user_id in self.lock_order() {
+            let row = Manager::row(&mut *tx, user_id)
+                .await?
+                .unwrap_or_else(|| GameRow::new(user_id));
+            rows.push(row);
+        }
+
+        for row in &rows {
+            row.verify_bet(self.bet)?;
+        }
+
+        let lock = ActiveGames::lock(ctx, self.opponent, self.game_id).await?;
+
+        for row in &mut rows {
+            row.bet(self.bet);
+        }
+
+        for user_id in self.players() {
+            LedgerHandler::coins(
+                &mut *tx,
+                user_id,
+                -self.bet,
+                LedgerSource::Game(self.game_id),
+            )
+            .await?;
+        }
+
+        let session = SessionRow::new(self.game_id, msg)
+            .stake(self.challenger, self.bet)
+            .stake(self.opponent, self.bet);
+
+        for row in rows {
+            Manager::save(&mut *tx, row).await?;
+        }
+        SessionHandler::save(&mut *tx, session).await?;
+        tx.commit().await?;
+
+        self.message = Some(msg.id);
+        self.locks.push(lock);
+
+        Ok(())
+    }
+
+    /// Pays the pot to the winner, or refunds both stakes when there is none,
+    /// and fires a game event for each player.
+    pub async fn settle<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        Manager: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+        SessionHandler: SessionManager<Db>,
+    >(
+        &self,
+        ctx: &Context,
+        pool: &Pool<Db>,
+        winner: Option<UserId>,
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        for user_id in self.lock_order() {
+            let mut row = Manager::row(&mut *tx, user_id)
+                .await?
+                .unwrap_or_else(|| GameRow::new(user_id));
+
+            let (payout, event) = match winner {
+                Some(winner) if winner == user_id => (
+                    self.winnings(),
+                    GameEvent::new(self.game_id, user_id, self.bet, true),
+                ),
+                Some(_) => (0, GameEvent::new(self.game_id, user_id, self.bet, false)),
+                None => (
+                    self.bet,
+                    GameEvent::new_draw(self.game_id, user_id, self.bet),
+                ),
+            };
+
+            row.add_coins(payout);
+            LedgerHandler::coins(&mut *tx, user_id, payout, LedgerSource::Game(self.game_id))
+                .await?;
+
+            Dispatch::<Db, EventHandler>::new(&mut *tx)
+                .fire(&mut row, Event::Game(event.set_payout(payout)))
+                .await?;
+
+            Manager::save(&mut *tx, row).await?;
+        }
+
+        // Closed with the payouts, so a restart either refunds both stakes or
+        // neither
+        if let Some(message) = self.message {
+            SessionHandler::delete(&mut *tx, message).await?;
+        }
+
+        tx.commit().await?;
+
+        for user_id in self.players() {
+            GameCache::update(ctx, user_id).await;
+        }
+
+        Ok(())
+    }
+
+    pub fn embed(&self, desc: impl Into<String>) -> CreateEmbed {
+        CreateEmbed::new().title(self.title).description(desc)
+    }
+}
+
+pub async fn ephemeral(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    content: &str,
+) -> Result<()> {
+    component
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -23,13 +23,19 @@ pub enum Error {
     GiftUsed(i64),
     SelfGift,
     SelfSend,
+    SelfChallenge,
     NegativeAmount,
     ZeroAmount,
     Cooldown(i64),
+    GameInProgress(&'static str),
     InvalidPrediction,
     InvalidAmount,
+    InvalidBet(String),
     ItemNotInInventory,
     InsufficientItemQuantity(i64),
+    GoalCompleted,
+    NoRerollAvailable,
+    RerollUsed(i64),
 
     Serenity(serenity::Error),
     Sqlx(sqlx::Error),
@@ -67,19 +73,35 @@ impl std::fmt::Display for Error {
             ),
             Error::SelfGift => write!(f, "You can't give yourself a gift... How selfish!"),
             Error::SelfSend => write!(f, "You cannot send funds to yourself"),
+            Error::SelfChallenge => write!(f, "You cannot challenge yourself"),
             Error::NegativeAmount => write!(f, "Amount cannot be negative"),
             Error::ZeroAmount => write!(f, "Amount cannot be 0"),
             Error::Cooldown(timestamp) => {
                 write!(f, "You are on a game cooldown. Try again <t:{timestamp}:R>")
             }
+            Error::GameInProgress(game) => {
+                write!(
+                    f,
+                    "Finish your current game of `{game}` before starting another"
+                )
+            }
             Error::InvalidPrediction => write!(f, "Invalid prediction value."),
             Error::InvalidAmount => write!(f, "Invalid amount value."),
+            Error::InvalidBet(bet) => write!(f, "`{bet}` is not a valid bet."),
             Error::ItemNotInInventory => write!(f, "You don't have that item in your inventory."),
             Error::InsufficientItemQuantity(quantity) => write!(
                 f,
                 "Cannot sell that many. You only have {} of this item.",
                 quantity.format()
             ),
+            Error::GoalCompleted => write!(f, "You can't reroll a goal you've already completed"),
+            Error::NoRerollAvailable => {
+                write!(f, "There are no other goals available to reroll into")
+            }
+            Error::RerollUsed(timestamp) => write!(
+                f,
+                "You can only reroll a goal once a day, try again <t:{timestamp}:R>"
+            ),
 
             Error::Serenity(e) => unimplemented!("Unhandled Serenity error: {e:?}"),
             Error::Sqlx(e) => unimplemented!("Unhandled SQLx error: {e:?}"),
@@ -1,30 +1,31 @@
 use std::marker::PhantomData;
 
-use sqlx::{Database, Pool};
+use sqlx::Database;
 
-use crate::GoalsManager;
-use crate::goals::GoalHandler;
+use super::{Event, EventRow, Subscriber};
 
-use super::{Event, EventRow};
-
-pub struct Dispatch<'a, Db: Database, Manager: GoalsManager<Db>> {
-    pool: &'a Pool<Db>,
-    _manager: PhantomData<Manager>,
+/// Fires events to `Handler` on the caller's connection, so everything the
+/// subscribers write commits or rolls back with the caller's transaction.
+pub struct Dispatch<'a, Db: Database, Handler: Subscriber<Db>> {
+    conn: &'a mut Db::Connection,
+    _handler: PhantomData<Handler>,
 }
 
-impl<'a, Db, Manager> Dispatch<'a, Db, Manager>
+impl<'a, Db, Handler> Dispatch<'a, Db, Handler>
 where
     Db: Database,
-    Manager: GoalsManager<Db>,
+    Handler: Subscriber<Db>,
 {
-    pub fn new(pool: &'a Pool<Db>) -> Self {
+    pub fn new(conn: &'a mut Db::Connection) -> Self {
         Self {
-            pool,
-            _manager: PhantomData,
+            conn,
+            _handler: PhantomData,
         }
     }
 
-    pub async fn fire(&self, row: &mut dyn EventRow, event: Event) -> sqlx::Result<Event> {
-        GoalHandler::process_goals::<Db, Manager>(self.pool, row, event).await
+    pub async fn fire(&mut self, row: &mut dyn EventRow, event: Event) -> sqlx::Result<Event> {
+        Handler::on_event(self.conn, row, &event).await?;
+
+        Ok(event)
     }
 }
@@ -1,9 +1,13 @@
 mod dispatch;
+mod subscriber;
+
+use std::collections::HashMap;
 
 pub use dispatch::Dispatch;
 use serenity::all::UserId;
+pub use subscriber::Subscriber;
 
-use crate::{Coins, Gems, MaxBet};
+use crate::{Coins, Gems, MaxBet, ShopCurrency};
 
 pub trait EventRow: Coins + Gems + MaxBet + Send + Sync {}
 
@@ -12,8 +16,17 @@ impl<T: Coins + Gems + MaxBet + Send + Sync> EventRow for T {}
 pub enum Event {
     Game(GameEvent),
     ShopPurchase(ShopPurchaseEvent),
+    Sell(SellEvent),
     Send(SendEvent),
+    /// Fired for the recipient of a send or gift
+    Receive(ReceiveEvent),
     Work(UserId),
+    Dig(DigEvent),
+    Craft(CraftEvent),
+    Prestige(PrestigeEvent),
+    Daily(DailyEvent),
+    LottoWin(LottoWinEvent),
+    ItemUse(ItemUseEvent),
 }
 
 impl Event {
@@ -22,7 +35,15 @@ impl Event {
             Self::Game(event) => event.user_id,
             Self::Work(id) => *id,
             Self::Send(event) => event.sender,
+            Self::Receive(event) => event.recipient,
             Self::ShopPurchase(event) => event.user_id,
+            Self::Sell(event) => event.user_id,
+            Self::Dig(event) => event.user_id,
+            Self::Craft(event) => event.user_id,
+            Self::Prestige(event) => event.user_id,
+            Self::Daily(event) => event.user_id,
+            Self::LottoWin(event) => event.user_id,
+            Self::ItemUse(event) => event.user_id,
         }
     }
 }
@@ -31,7 +52,13 @@ pub struct GameEvent {
     pub game_id: String,
     pub user_id: UserId,
     pub bet: i64,
+    /// The final payout, after effects are applied
+    pub payout: i64,
+    /// The item ids of the effects consumed by the game
+    pub effects: Vec<String>,
     pub win: bool,
+    pub draw: bool,
+    pub streak: i64,
 }
 
 impl GameEvent {
@@ -40,9 +67,35 @@ impl GameEvent {
             game_id: id.into(),
             user_id: user_id.into(),
             bet,
+            payout: 0,
+            effects: Vec::new(),
             win,
+            draw: false,
+            streak: 0,
         }
     }
+
+    pub fn new_draw(id: impl Into<String>, user_id: impl Into<UserId>, bet: i64) -> Self {
+        Self {
+            draw: true,
+            ..Self::new(id, user_id, bet, false)
+        }
+    }
+
+    pub fn set_streak(mut self, streak: i64) -> Self {
+        self.streak = streak;
+        self
+    }
+
+    pub fn set_payout(mut self, payout: i64) -> Self {
+        self.payout = payout;
+        self
+    }
+
+    pub fn set_effects(mut self, effects: Vec<String>) -> Self {
+        self.effects = effects;
+        self
+    }
 }
 
 pub struct ShopPurchaseEvent {
@@ -72,3 +125,136 @@ impl SendEvent {
         }
     }
 }
+
+pub struct ReceiveEvent {
+    pub amount: i64,
+    pub sender: UserId,
+    pub recipient: UserId,
+}
+
+impl ReceiveEvent {
+    pub fn new(amount: i64, sender: impl Into<UserId>, recipient: impl Into<UserId>) -> Self {
+        Self {
+            amount,
+            sender: sender.into(),
+            recipient: recipient.into(),
+        }
+    }
+}
+
+pub struct SellEvent {
+    pub user_id: UserId,
+    pub item_id: String,
+    pub amount: i64,
+    pub payment: i64,
+}
+
+impl SellEvent {
+    pub fn new(
+        user_id: impl Into<UserId>,
+        item_id: impl Into<String>,
+        amount: i64,
+        payment: i64,
+    ) -> Self {
+        Self {
+            user_id: user_id.into(),
+            item_id: item_id.into(),
+            amount,
+            payment,
+        }
+    }
+}
+
+pub struct DigEvent {
+    pub user_id: UserId,
+    /// The amount found of each resource, including the ones not found
+    pub resources: HashMap<&'static str, i64>,
+}
+
+impl DigEvent {
+    pub fn new(user_id: impl Into<UserId>, resources: HashMap<&'static str, i64>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            resources,
+        }
+    }
+
+    pub fn amount(&self, resource: &str) -> i64 {
+        self.resources.get(resource).copied().unwrap_or_default()
+    }
+}
+
+pub struct CraftEvent {
+    pub user_id: UserId,
+    pub item: ShopCurrency,
+    pub amount: i64,
+}
+
+impl CraftEvent {
+    pub fn new(user_id: impl Into<UserId>, item: ShopCurrency, amount: i64) -> Self {
+        Self {
+            user_id: user_id.into(),
+            item,
+            amount,
+        }
+    }
+}
+
+pub struct PrestigeEvent {
+    pub user_id: UserId,
+    /// The prestige reached
+    pub prestige: i64,
+}
+
+impl PrestigeEvent {
+    pub fn new(user_id: impl Into<UserId>, prestige: i64) -> Self {
+        Self {
+            user_id: user_id.into(),
+            prestige,
+        }
+    }
+}
+
+pub struct DailyEvent {
+    pub user_id: UserId,
+    pub amount: i64,
+}
+
+impl DailyEvent {
+    pub fn new(user_id: impl Into<UserId>, amount: i64) -> Self {
+        Self {
+            user_id: user_id.into(),
+            amount,
+        }
+    }
+}
+
+pub struct LottoWinEvent {
+    pub user_id: UserId,
+    pub payout: i64,
+}
+
+impl LottoWinEvent {
+    pub fn new(user_id: impl Into<UserId>, payout: i64) -> Self {
+        Self {
+            user_id: user_id.into(),
+            payout,
+        }
+    }
+}
+
+pub struct ItemUseEvent {
+    pub user_id: UserId,
+    pub item_id: String,
+    pub amount: i64,
+}
+
+impl ItemUseEvent {
+    pub fn new(user_id: impl Into<UserId>, item_id: impl Into<String>, amount: i64) -> Self {
+        Self {
+            user_id: user_id.into(),
+            item_id: item_id.into(),
+            amount,
+        }
+    }
+}
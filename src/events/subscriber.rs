@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use sqlx::Database;
+
+use super::{Event, EventRow};
+
+/// A reaction to the events fired through [`Dispatch`](super::Dispatch).
+///
+/// Subscribers are combined as tuples and run in order, e.g.
+/// `(GoalsSubscriber<Goals, Ledger>, StatsSubscriber<Stats>)`.
+///
+/// The ledger is not a subscriber. Bets and other balance changes that fire
+/// no event need entries too, so every entry is written where the balance
+/// changes, including inside other subscribers.
+#[async_trait]
+pub trait Subscriber<Db: Database> {
+    async fn on_event(
+        conn: &mut Db::Connection,
+        row: &mut dyn EventRow,
+        event: &Event,
+    ) -> sqlx::Result<()>;
+}
+
+macro_rules! impl_subscriber_tuple {
+    ($($handler:ident),+) => {
+        #[async_trait]
+        impl<Db: Database, $($handler: Subscriber<Db>),+> Subscriber<Db> for ($($handler,)+) {
+            async fn on_event(
+                conn: &mut Db::Connection,
+                row: &mut dyn EventRow,
+                event: &Event,
+            ) -> sqlx::Result<()> {
+                $($handler::on_event(conn, row, event).await?;)+
+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_subscriber_tuple!(A);
+impl_subscriber_tuple!(A, B);
+impl_subscriber_tuple!(A, B, C);
+impl_subscriber_tuple!(A, B, C, D);
+impl_subscriber_tuple!(A, B, C, D, E);
+impl_subscriber_tuple!(A, B, C, D, E, F);
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::sync::RwLockReadGuard;
+use std::sync::{Arc, Mutex, RwLockReadGuard};
 
 use chrono::{DateTime, Duration, Utc};
 use serenity::all::{Context, UserId};
@@ -49,3 +49,76 @@ impl GameCache {
 impl TypeMapKey for GameCache {
     type Value = GameCache;
 }
+
+// Games that wait on other players, so they may run alongside other games,
+// but never alongside another game of their kind. Every other game locks the
+// player out of all other games until it ends.
+const SHARED_GAMES: [&str; 5] = ["coinflip", "roll", "rps", "tictactoe", "poker"];
+
+fn can_overlap(game_id: &str, other: &str) -> bool {
+    game_id != other && SHARED_GAMES.contains(&game_id) && SHARED_GAMES.contains(&other)
+}
+
+/// The interactive games each player has running.
+#[derive(Clone, Default)]
+pub struct ActiveGames(Arc<Mutex<HashMap<UserId, Vec<&'static str>>>>);
+
+impl ActiveGames {
+    /// Marks `game_id` as running for the player until the returned lock is
+    /// dropped, so it is released however the game ends.
+    pub async fn lock(
+        ctx: &Context,
+        id: impl Into<UserId>,
+        game_id: &'static str,
+    ) -> Result<GameLock> {
+        let games = {
+            let mut data = ctx.data.write().await;
+            data.entry::<ActiveGames>().or_default().clone()
+        };
+
+        let user_id = id.into();
+
+        {
+            let mut active = games.0.lock().unwrap();
+            let running = active.entry(user_id).or_default();
+
+            if let Some(&other) = running.iter().find(|other| !can_overlap(game_id, other)) {
+                return Err(Error::GameInProgress(other));
+            }
+
+            running.push(game_id);
+        }
+
+        Ok(GameLock {
+            games,
+            user_id,
+            game_id,
+        })
+    }
+}
+
+impl TypeMapKey for ActiveGames {
+    type Value = ActiveGames;
+}
+
+pub struct GameLock {
+    games: ActiveGames,
+    user_id: UserId,
+    game_id: &'static str,
+}
+
+impl Drop for GameLock {
+    fn drop(&mut self) {
+        let mut active = self.games.0.lock().unwrap();
+
+        if let Some(running) = active.get_mut(&self.user_id) {
+            if let Some(i) = running.iter().position(|game| *game == self.game_id) {
+                running.swap_remove(i);
+            }
+
+            if running.is_empty() {
+                active.remove(&self.user_id);
+            }
+        }
+    }
+}
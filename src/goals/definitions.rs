@@ -2,12 +2,12 @@ use std::collections::HashMap;
 use std::sync::LazyLock;
 
 use rand::rng;
-use rand::seq::IteratorRandom;
+use rand::seq::SliceRandom;
 use zayden_core::FormatNum;
 
-use crate::GamblingGoalsRow;
 use crate::events::{Event, EventRow};
 use crate::shop::LOTTO_TICKET;
+use crate::{GamblingGoalsRow, GoalPeriod};
 
 #[derive(Clone, Copy)]
 pub struct GoalDefinition {
@@ -15,32 +15,63 @@ pub struct GoalDefinition {
     pub target: fn(&dyn EventRow) -> i64,
     pub description: fn(i64) -> String,
     pub update_fn: fn(&mut GamblingGoalsRow, &Event) -> bool,
+    /// The periods the goal can be picked for. Weekly and monthly goals
+    /// multiply the target, so only goals that accumulate progress fit them.
+    pub periods: &'static [GoalPeriod],
+    /// Goals of the same category are never picked together
+    pub category: &'static str,
+    /// Whether the goal is achievable for the player right now
+    pub available: fn(&dyn EventRow) -> bool,
 }
 
 impl GoalDefinition {
-    const fn new(id: &'static str) -> Self {
+    pub const fn new(id: &'static str) -> Self {
         Self {
             id,
             target: |_| 1,
             description: |_| String::new(),
             update_fn: |_, _| false,
+            periods: &[GoalPeriod::Daily],
+            category: id,
+            available: |_| true,
         }
     }
 
-    const fn set_target(mut self, f: fn(&dyn EventRow) -> i64) -> Self {
+    pub const fn set_target(mut self, f: fn(&dyn EventRow) -> i64) -> Self {
         self.target = f;
         self
     }
 
-    const fn set_description(mut self, f: fn(i64) -> String) -> Self {
+    pub const fn set_description(mut self, f: fn(i64) -> String) -> Self {
         self.description = f;
         self
     }
 
-    const fn set_update_fn(mut self, f: fn(&mut GamblingGoalsRow, &Event) -> bool) -> Self {
+    pub const fn set_update_fn(mut self, f: fn(&mut GamblingGoalsRow, &Event) -> bool) -> Self {
         self.update_fn = f;
         self
     }
+
+    pub const fn set_periods(mut self, periods: &'static [GoalPeriod]) -> Self {
+        self.periods = periods;
+        self
+    }
+
+    pub const fn set_category(mut self, category: &'static str) -> Self {
+        self.category = category;
+        self
+    }
+
+    pub const fn set_available(mut self, f: fn(&dyn EventRow) -> bool) -> Self {
+        self.available = f;
+        self
+    }
+}
+
+/// Scales a count target with the player's progression, +10% per prestige
+/// and +1% per level.
+fn scaled(row: &dyn EventRow, base: i64) -> i64 {
+    base * (100 + row.prestige() * 10 + row.level() as i64) / 100
 }
 
 const LOTTO: GoalDefinition = GoalDefinition::new("lotto")
@@ -61,6 +92,7 @@ const LOTTO: GoalDefinition = GoalDefinition::new("lotto")
 
 const GIFT: GoalDefinition = GoalDefinition::new("gift")
     .set_description(|_| String::from("Send a gift"))
+    .set_category("send")
     .set_update_fn(|goal: &mut GamblingGoalsRow, event: &Event| {
         let Event::Send(event) = event else {
             return false;
@@ -74,9 +106,11 @@ const GIFT: GoalDefinition = GoalDefinition::new("gift")
         true
     });
 
-const WIN_10: GoalDefinition = GoalDefinition::new("gift")
-    .set_target(|_| rand::random_range(7..=10))
+const WIN_10: GoalDefinition = GoalDefinition::new("win10")
+    .set_target(|row| scaled(row, rand::random_range(7..=10)))
     .set_description(|t| format!("Win {t} times"))
+    .set_category("win")
+    .set_periods(&GoalPeriod::ALL)
     .set_update_fn(|goal: &mut GamblingGoalsRow, event: &Event| {
         let Event::Game(event) = event else {
             return false;
@@ -102,14 +136,15 @@ const HIGHERLOWER: GoalDefinition = GoalDefinition::new("higherlower")
             return false;
         }
 
-        goal.progress = event.bet / 1000;
-        goal.progress = goal.progress.min(goal.target);
+        goal.progress = goal.progress.max(event.streak.min(goal.target));
         true
     });
 
 const WIN_MAX_BET: GoalDefinition = GoalDefinition::new("winmaxbet")
     .set_target(|row| row.max_bet().min(row.coins()))
     .set_description(|t| format!("Win {} coins", t.format()))
+    .set_category("win")
+    .set_periods(&GoalPeriod::ALL)
     .set_update_fn(|goal, event| {
         let Event::Game(event) = event else {
             return false;
@@ -126,11 +161,17 @@ const WIN_MAX_BET: GoalDefinition = GoalDefinition::new("winmaxbet")
 const WIN_3_ROW: GoalDefinition = GoalDefinition::new("win3row")
     .set_target(|_| 3)
     .set_description(|_| String::from("Win 3 times in a row"))
+    .set_category("win")
     .set_update_fn(|goal, event| {
         let Event::Game(event) = event else {
             return false;
         };
 
+        // A draw neither extends nor breaks the streak
+        if event.draw {
+            return false;
+        }
+
         if !event.win {
             goal.reset_progress();
             return false;
@@ -143,6 +184,8 @@ const WIN_3_ROW: GoalDefinition = GoalDefinition::new("win3row")
 const ALL_IN: GoalDefinition = GoalDefinition::new("allin")
     .set_target(|row| row.coins().max(1000).min(row.max_bet()))
     .set_description(|t| format!("Go all in ({})", t.format()))
+    // The target is at least the minimum all in of 1,000
+    .set_available(|row| row.coins() >= 1000)
     .set_update_fn(|goal, event| {
         let Event::Game(event) = event else {
             return false;
@@ -155,6 +198,8 @@ const ALL_IN: GoalDefinition = GoalDefinition::new("allin")
 const SEND_COINS: GoalDefinition = GoalDefinition::new("sendcoins")
     .set_target(|row| (row.coins() / 10).min(row.max_bet() / 10).max(2500))
     .set_description(|t| format!("Send coins ({})", t.format()))
+    .set_category("send")
+    .set_periods(&GoalPeriod::ALL)
     .set_update_fn(|goal, event| {
         let Event::Send(event) = event else {
             return false;
@@ -165,43 +210,159 @@ const SEND_COINS: GoalDefinition = GoalDefinition::new("sendcoins")
     });
 
 const WORK: GoalDefinition = GoalDefinition::new("work")
-    .set_target(|_| rand::random_range(3..=7))
+    .set_target(|row| scaled(row, rand::random_range(3..=7)))
     .set_description(|t| format!("Work or Dig {t}x times"))
+    .set_periods(&GoalPeriod::ALL)
     .set_update_fn(|goal, event| {
-        let Event::Work(_) = event else {
+        if !matches!(event, Event::Work(_) | Event::Dig(_)) {
             return false;
-        };
+        }
 
         goal.update_progress(1);
         true
     });
 
+#[derive(Default)]
+pub struct GoalRegistryBuilder(Vec<GoalDefinition>);
+
+impl GoalRegistryBuilder {
+    pub fn goal(mut self, goal: GoalDefinition) -> Self {
+        self.0.push(goal);
+        self
+    }
+
+    pub fn goals(mut self, goals: impl IntoIterator<Item = GoalDefinition>) -> Self {
+        self.0.extend(goals);
+        self
+    }
+
+    /// # Panics
+    ///
+    /// If two goals share an id, as one would otherwise silently replace the
+    /// other.
+    pub fn build(self) -> GoalRegistry {
+        let mut goals = HashMap::with_capacity(self.0.len());
+
+        for goal in self.0 {
+            if goals.insert(goal.id, goal).is_some() {
+                panic!("Duplicate goal id: {}", goal.id);
+            }
+        }
+
+        GoalRegistry(goals)
+    }
+}
+
 pub struct GoalRegistry(HashMap<&'static str, GoalDefinition>);
 
 impl GoalRegistry {
-    pub fn new(goals: [GoalDefinition; 9]) -> Self {
-        Self(goals.into_iter().map(|goal| (goal.id, goal)).collect())
+    pub fn builder() -> GoalRegistryBuilder {
+        GoalRegistryBuilder::default()
     }
 
     pub fn get_definition(&self, id: &str) -> Option<GoalDefinition> {
         self.0.get(id).copied()
     }
 
-    pub fn select_daily_goal(&self) -> Vec<GoalDefinition> {
-        self.0.values().copied().choose_multiple(&mut rng(), 3)
+    /// Whether the goal still has a definition that can be picked for its
+    /// period. Goals that don't were removed or renamed since they were saved.
+    pub fn resolves(&self, goal: &GamblingGoalsRow) -> bool {
+        // Daily goals saved as "gift" before the ids were made unique belong
+        // to the old "Win N times" goal, the gift goal always targets 1
+        if goal.goal_id() == GIFT.id && goal.target != 1 {
+            return false;
+        }
+
+        self.get_definition(goal.goal_id())
+            .is_some_and(|definition| definition.periods.contains(&goal.period()))
+    }
+
+    pub fn select_goals(&self, period: GoalPeriod, row: &dyn EventRow) -> Vec<GoalDefinition> {
+        self.pick(period, row, &[], period.goal_count())
+    }
+
+    /// Picks a goal to replace one of `current`, that shares a category
+    /// with none of them.
+    pub fn reroll(
+        &self,
+        period: GoalPeriod,
+        row: &dyn EventRow,
+        current: &[GamblingGoalsRow],
+    ) -> Option<GoalDefinition> {
+        let current = current
+            .iter()
+            .filter_map(|goal| self.get_definition(goal.goal_id()))
+            .collect::<Vec<_>>();
+
+        self.pick(period, row, &current, 1).pop()
+    }
+
+    fn pick(
+        &self,
+        period: GoalPeriod,
+        row: &dyn EventRow,
+        exclude: &[GoalDefinition],
+        count: usize,
+    ) -> Vec<GoalDefinition> {
+        let mut candidates = self
+            .0
+            .values()
+            .filter(|goal| goal.periods.contains(&period) && (goal.available)(row))
+            .copied()
+            .collect::<Vec<_>>();
+
+        candidates.shuffle(&mut rng());
+
+        candidates
+            .into_iter()
+            .fold(Vec::with_capacity(count), |mut picked, goal| {
+                let taken = picked
+                    .iter()
+                    .chain(exclude)
+                    .any(|other| other.category == goal.category);
+
+                if picked.len() < count && !taken {
+                    picked.push(goal);
+                }
+
+                picked
+            })
     }
 }
 
 pub static GOAL_REGISTRY: LazyLock<GoalRegistry> = LazyLock::new(|| {
-    GoalRegistry::new([
-        LOTTO,
-        GIFT,
-        WIN_10,
-        HIGHERLOWER,
-        WIN_MAX_BET,
-        WIN_3_ROW,
-        ALL_IN,
-        SEND_COINS,
-        WORK,
-    ])
+    GoalRegistry::builder()
+        .goals([
+            LOTTO,
+            GIFT,
+            WIN_10,
+            HIGHERLOWER,
+            WIN_MAX_BET,
+            WIN_3_ROW,
+            ALL_IN,
+            SEND_COINS,
+            WORK,
+        ])
+        .build()
 });
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::UserId;
+
+    use super::*;
+
+    #[test]
+    fn registry_has_unique_ids() {
+        LazyLock::force(&GOAL_REGISTRY);
+    }
+
+    #[test]
+    fn legacy_gift_goals_are_stale() {
+        let legacy = GamblingGoalsRow::new(UserId::new(1), GIFT.id, GoalPeriod::Daily, 8);
+        let current = GamblingGoalsRow::new(UserId::new(1), GIFT.id, GoalPeriod::Daily, 1);
+
+        assert!(!GOAL_REGISTRY.resolves(&legacy));
+        assert!(GOAL_REGISTRY.resolves(&current));
+    }
+}
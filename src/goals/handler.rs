@@ -1,63 +1,145 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
 use serenity::all::UserId;
-use sqlx::{Database, Pool};
+use sqlx::Database;
 
 use crate::GamblingGoalsRow;
+use crate::GoalPeriod;
 use crate::GoalsManager;
 use crate::events::Event;
 use crate::events::EventRow;
+use crate::events::Subscriber;
+use crate::ledger::{LedgerManager, LedgerSource};
 
-use super::GOAL_REGISTRY;
+use super::{GOAL_REGISTRY, GoalDefinition};
 
 pub struct GoalHandler;
 
 impl GoalHandler {
-    pub async fn daily_reset<Db: Database, Manager: GoalsManager<Db>>(
-        pool: &Pool<Db>,
+    pub async fn period_reset<Db: Database, Manager: GoalsManager<Db>>(
+        conn: &mut Db::Connection,
         id: impl Into<UserId>,
+        period: GoalPeriod,
         row: &dyn EventRow,
     ) -> sqlx::Result<Vec<GamblingGoalsRow>> {
         let id = id.into();
 
-        let selected_goal_definitions = GOAL_REGISTRY.select_daily_goal();
-
-        let goals = selected_goal_definitions
+        let goals = GOAL_REGISTRY
+            .select_goals(period, row)
             .into_iter()
-            .map(|goal| {
-                let target_value = (goal.target)(row);
-                (goal.id, target_value)
-            })
-            .map(|(goal_id, target)| GamblingGoalsRow::new(id, goal_id, target))
+            .map(|goal| Self::new_goal(id, goal, period, row))
             .collect::<Vec<_>>();
 
-        let rows = Manager::update(pool, &goals).await?;
+        let rows = Manager::reset(conn, id, period, &goals).await?;
 
         Ok(rows)
     }
 
+    /// Replaces the daily goal at `index` with a goal of another category,
+    /// returning the daily goals. `None` if there is no goal to replace it
+    /// with.
+    pub async fn reroll<Db: Database, Manager: GoalsManager<Db>>(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId>,
+        row: &dyn EventRow,
+        mut goals: Vec<GamblingGoalsRow>,
+        index: usize,
+    ) -> sqlx::Result<Option<Vec<GamblingGoalsRow>>> {
+        let id = id.into();
+
+        if index >= goals.len() {
+            return Ok(None);
+        }
+
+        let Some(goal) = GOAL_REGISTRY.reroll(GoalPeriod::Daily, row, &goals) else {
+            return Ok(None);
+        };
+
+        goals[index] = Self::new_goal(id, goal, GoalPeriod::Daily, row);
+
+        let rows = Manager::reset(conn, id, GoalPeriod::Daily, &goals).await?;
+
+        Ok(Some(rows))
+    }
+
+    fn new_goal(
+        id: UserId,
+        goal: GoalDefinition,
+        period: GoalPeriod,
+        row: &dyn EventRow,
+    ) -> GamblingGoalsRow {
+        let target = (goal.target)(row) * period.target_multiplier();
+        GamblingGoalsRow::new(id, goal.id, period, target)
+    }
+
+    /// The goals of every period, resetting the periods that have ended.
     pub async fn get_user_progress<Db: Database, Manager: GoalsManager<Db>>(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         user_id: impl Into<UserId>,
         row: &dyn EventRow,
     ) -> sqlx::Result<Vec<GamblingGoalsRow>> {
         let user_id = user_id.into();
 
-        let mut goals = Manager::full_rows(pool, user_id).await?;
+        let mut rows = Manager::full_rows(&mut *conn, user_id).await?;
+        let mut goals = Vec::with_capacity(rows.len());
+
+        for period in GoalPeriod::ALL {
+            let (period_goals, rest): (Vec<_>, Vec<_>) =
+                rows.into_iter().partition(|goal| goal.period() == period);
+            rows = rest;
+
+            let period_goals = if period_goals.first().is_some_and(|goal| goal.is_current()) {
+                Self::migrate::<Db, Manager>(&mut *conn, user_id, period, row, period_goals).await?
+            } else {
+                Self::period_reset::<Db, Manager>(&mut *conn, user_id, period, row).await?
+            };
 
-        if goals.is_empty() || !goals[0].is_today() {
-            goals = Self::daily_reset::<Db, Manager>(pool, user_id, row).await?;
+            goals.extend(period_goals);
         }
 
         Ok(goals)
     }
 
-    pub async fn process_goals<Db: Database, Manager: GoalsManager<Db>>(
-        pool: &Pool<Db>,
+    /// Replaces the goals that no longer resolve to a definition, keeping the
+    /// progress of the rest of the period.
+    async fn migrate<Db: Database, Manager: GoalsManager<Db>>(
+        conn: &mut Db::Connection,
+        id: UserId,
+        period: GoalPeriod,
+        row: &dyn EventRow,
+        goals: Vec<GamblingGoalsRow>,
+    ) -> sqlx::Result<Vec<GamblingGoalsRow>> {
+        let (mut goals, stale): (Vec<_>, Vec<_>) = goals
+            .into_iter()
+            .partition(|goal| GOAL_REGISTRY.resolves(goal));
+
+        if stale.is_empty() {
+            return Ok(goals);
+        }
+
+        for _ in stale {
+            if let Some(goal) = GOAL_REGISTRY.reroll(period, row, &goals) {
+                goals.push(Self::new_goal(id, goal, period, row));
+            }
+        }
+
+        Manager::reset(conn, id, period, &goals).await
+    }
+
+    pub async fn process_goals<
+        Db: Database,
+        Manager: GoalsManager<Db>,
+        Ledger: LedgerManager<Db>,
+    >(
+        conn: &mut Db::Connection,
         row: &mut dyn EventRow,
-        event: Event,
-    ) -> sqlx::Result<Event> {
+        event: &Event,
+    ) -> sqlx::Result<()> {
         let user_id = event.user_id();
 
-        let mut all_goals = Self::get_user_progress::<Db, Manager>(pool, user_id, row).await?;
+        let mut all_goals =
+            Self::get_user_progress::<Db, Manager>(&mut *conn, user_id, row).await?;
 
         let changed = all_goals
             .iter_mut()
@@ -68,7 +150,7 @@ impl GoalHandler {
                     .map(|definition| (goal, definition))
             })
             .fold(Vec::new(), |mut acc, (goal, definition)| {
-                let changed = (definition.update_fn)(goal, &event);
+                let changed = (definition.update_fn)(goal, event);
 
                 if changed {
                     acc.push(goal);
@@ -77,19 +159,59 @@ impl GoalHandler {
                 acc
             });
 
-        changed
+        let reward = changed
             .iter()
             .filter(|goal| goal.is_complete())
-            .for_each(|_| row.add_coins(5_000));
+            .map(|goal| goal.period().coin_reward())
+            .sum::<i64>();
 
-        if !changed.is_empty() {
-            if all_goals.iter().all(|row| row.is_complete()) {
-                row.add_gems(1);
-            }
+        let changed_periods = changed.iter().map(|goal| goal.period()).collect::<Vec<_>>();
 
-            Manager::update(pool, &all_goals).await.unwrap();
+        let gems = GoalPeriod::ALL
+            .into_iter()
+            .filter(|period| changed_periods.contains(period))
+            .filter(|period| {
+                all_goals
+                    .iter()
+                    .filter(|goal| goal.period() == *period)
+                    .all(|goal| goal.is_complete())
+            })
+            .map(|period| period.gem_reward())
+            .sum::<i64>();
+
+        if reward > 0 {
+            row.add_coins(reward);
+            Ledger::coins(&mut *conn, user_id, reward, LedgerSource::Goal).await?;
+        }
+
+        if gems > 0 {
+            row.add_gems(gems);
+            Ledger::gems(&mut *conn, user_id, gems, LedgerSource::Goal).await?;
+        }
+
+        if !changed_periods.is_empty() {
+            Manager::update(conn, &all_goals).await?;
         }
 
-        Ok(event)
+        Ok(())
+    }
+}
+
+/// Progresses the daily goals on every event.
+pub struct GoalsSubscriber<Manager, Ledger>(PhantomData<(Manager, Ledger)>);
+
+#[async_trait]
+impl<Db, Manager, Ledger> Subscriber<Db> for GoalsSubscriber<Manager, Ledger>
+where
+    Db: Database,
+    Manager: GoalsManager<Db>,
+    Ledger: LedgerManager<Db>,
+{
+    async fn on_event(
+        conn: &mut Db::Connection,
+        row: &mut dyn EventRow,
+        event: &Event,
+    ) -> sqlx::Result<()> {
+        GoalHandler::process_goals::<Db, Manager, Ledger>(conn, row, event).await
     }
 }
@@ -1,5 +1,5 @@
 mod definitions;
 mod handler;
 
-pub use definitions::GOAL_REGISTRY;
-pub use handler::GoalHandler;
+pub use definitions::{GOAL_REGISTRY, GoalDefinition, GoalRegistry, GoalRegistryBuilder};
+pub use handler::{GoalHandler, GoalsSubscriber};
@@ -0,0 +1,109 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use serenity::all::UserId;
+use sqlx::{Database, FromRow, any::AnyQueryResult};
+
+#[async_trait]
+pub trait LedgerManager<Db: Database> {
+    /// The ledger is append-only, entries are never updated or deleted.
+    async fn record(conn: &mut Db::Connection, row: LedgerRow) -> sqlx::Result<AnyQueryResult>;
+
+    async fn coins(
+        conn: &mut Db::Connection,
+        user_id: impl Into<UserId> + Send,
+        delta: i64,
+        source: LedgerSource<'_>,
+    ) -> sqlx::Result<()> {
+        if delta != 0 {
+            Self::record(conn, LedgerRow::new(user_id, delta, "coins", source)).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn gems(
+        conn: &mut Db::Connection,
+        user_id: impl Into<UserId> + Send,
+        delta: i64,
+        source: LedgerSource<'_>,
+    ) -> sqlx::Result<()> {
+        if delta != 0 {
+            Self::record(conn, LedgerRow::new(user_id, delta, "gems", source)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A single coin or gem balance change.
+#[derive(FromRow)]
+pub struct LedgerRow {
+    pub user_id: i64,
+    pub delta: i64,
+    pub currency: String,
+    pub source: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl LedgerRow {
+    pub fn new(
+        user_id: impl Into<UserId>,
+        delta: i64,
+        currency: &str,
+        source: LedgerSource<'_>,
+    ) -> Self {
+        Self {
+            user_id: user_id.into().get() as i64,
+            delta,
+            currency: currency.to_string(),
+            source: source.to_string(),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum LedgerSource<'a> {
+    /// Bets and payouts, by game id
+    Game(&'a str),
+    /// Stakes returned after a game was interrupted by a restart
+    Refund(&'a str),
+    Buy(&'a str),
+    Sell(&'a str),
+    Send,
+    Gift,
+    Daily,
+    Work,
+    Dig,
+    Lotto,
+    Goal,
+    /// Gems paid to reroll a goal
+    Reroll,
+    /// Rewards for unlocking an achievement, by achievement id
+    Achievement(&'a str),
+    /// Coins reset and gems awarded on prestige
+    Prestige,
+}
+
+impl Display for LedgerSource<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Game(game_id) => write!(f, "game:{game_id}"),
+            Self::Refund(game_id) => write!(f, "refund:{game_id}"),
+            Self::Buy(item_id) => write!(f, "buy:{item_id}"),
+            Self::Sell(item_id) => write!(f, "sell:{item_id}"),
+            Self::Send => write!(f, "send"),
+            Self::Gift => write!(f, "gift"),
+            Self::Daily => write!(f, "daily"),
+            Self::Work => write!(f, "work"),
+            Self::Dig => write!(f, "dig"),
+            Self::Lotto => write!(f, "lotto"),
+            Self::Goal => write!(f, "goal"),
+            Self::Reroll => write!(f, "reroll"),
+            Self::Achievement(achievement_id) => write!(f, "achievement:{achievement_id}"),
+            Self::Prestige => write!(f, "prestige"),
+        }
+    }
+}
@@ -5,30 +5,46 @@ use chrono::Utc;
 use serenity::all::EmojiId;
 use serenity::all::UserId;
 
+pub mod achievements;
 pub mod commands;
+pub mod config;
+pub mod duel;
 pub mod error;
 pub mod events;
 pub mod game_cache;
 pub mod goals;
+pub mod ledger;
 pub mod lotto;
 pub mod models;
+pub mod notifications;
+pub mod poker;
+pub mod session_store;
 pub mod shop;
 pub mod stamina;
+pub mod stats;
+pub mod tictactoe;
 pub mod utils;
 
+pub use achievements::{AchievementRow, AchievementsManager, AchievementsSubscriber};
 pub use commands::Commands;
 pub use commands::goals::GoalsManager;
+pub use commands::history::{HistoryManager, HistoryRow, HistorySubscriber};
+pub use config::GamblingConfig;
 pub use error::Error;
 use error::Result;
-pub use game_cache::GameCache;
-pub use goals::GoalHandler;
+pub use game_cache::{ActiveGames, GameCache, GameLock};
+pub use goals::{GoalHandler, GoalsSubscriber};
+pub use ledger::{LedgerManager, LedgerRow, LedgerSource};
 pub use lotto::{Lotto, LottoManager, LottoRow, jackpot};
 pub use models::{
     Coins, EffectsManager, EffectsRow, GamblingGoalsRow, GamblingItem, GameManager, GameRow, Gems,
-    ItemInventory, MaxBet, MaxValues, MineHourly, Mining, Prestige, Stamina, VerifyBet,
+    GoalPeriod, ItemInventory, MaxBet, MaxValues, MineHourly, Mining, Prestige, Stamina, VerifyBet,
 };
+pub use notifications::{NotificationsManager, NotificationsSubscriber};
+pub use session_store::{SessionManager, SessionRow, SessionStore};
 pub use shop::{SHOP_ITEMS, ShopCurrency, ShopItem, ShopPage};
 pub use stamina::{StaminaCron, StaminaManager};
+pub use stats::{GameStatsRow, StatsManager, StatsSubscriber};
 
 const SUPER_USER: UserId = UserId::new(211486447369322506);
 
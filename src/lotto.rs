@@ -7,8 +7,9 @@ use sqlx::any::AnyQueryResult;
 use sqlx::{Database, FromRow};
 use zayden_core::{CronJob, FormatNum};
 
+use crate::events::{Dispatch, Event, LottoWinEvent, Subscriber};
 use crate::shop::LOTTO_TICKET;
-use crate::{COIN, Coins};
+use crate::{COIN, Coins, Gems, LedgerManager, LedgerSource, MaxBet, Prestige};
 
 const CHANNEL_ID: ChannelId = ChannelId::new(1383573049563156502);
 
@@ -30,12 +31,19 @@ pub trait LottoManager<Db: Database> {
         id: impl Into<UserId> + Send,
         amount: i64,
     ) -> sqlx::Result<AnyQueryResult>;
+
+    /// Saves what the event subscribers changed on a winner, tickets are
+    /// cleared by `delete_tickets`.
+    async fn save(conn: &mut Db::Connection, row: LottoRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(FromRow)]
 pub struct LottoRow {
     pub id: i64,
     pub coins: i64,
+    pub gems: i64,
+    pub level: Option<i32>,
+    pub prestige: i64,
     pub quantity: Option<i64>,
 }
 
@@ -46,6 +54,9 @@ impl LottoRow {
         Self {
             id: id.get() as i64,
             coins: 0,
+            gems: 0,
+            level: Some(0),
+            prestige: 0,
             quantity: Some(0),
         }
     }
@@ -69,6 +80,28 @@ impl Coins for LottoRow {
     }
 }
 
+impl Gems for LottoRow {
+    fn gems(&self) -> i64 {
+        self.gems
+    }
+
+    fn gems_mut(&mut self) -> &mut i64 {
+        &mut self.gems
+    }
+}
+
+impl Prestige for LottoRow {
+    fn prestige(&self) -> i64 {
+        self.prestige
+    }
+}
+
+impl MaxBet for LottoRow {
+    fn level(&self) -> i32 {
+        self.level.unwrap_or_default()
+    }
+}
+
 #[inline]
 pub fn jackpot(tickets: i64) -> i64 {
     (tickets * LOTTO_TICKET.coin_cost().unwrap()).max(1_000_000)
@@ -77,8 +110,15 @@ pub fn jackpot(tickets: i64) -> i64 {
 pub struct Lotto;
 
 impl Lotto {
-    pub fn cron_job<Db: Database, Manager: LottoManager<Db>>() -> CronJob<Db> {
-        CronJob::new("0 0 17 * * Fri *").set_action(|ctx, pool| async move {
+    pub const SCHEDULE: &str = "0 0 17 * * Fri *";
+
+    pub fn cron_job<
+        Db: Database,
+        EventHandler: Subscriber<Db>,
+        Manager: LottoManager<Db>,
+        Ledger: LedgerManager<Db>,
+    >() -> CronJob<Db> {
+        CronJob::new(Self::SCHEDULE).set_action(|ctx, pool| async move {
             let mut tx: sqlx::Transaction<'static, Db> = pool.begin().await.unwrap();
 
             let mut rows = Manager::rows(&mut *tx).await.unwrap();
@@ -109,11 +149,35 @@ impl Lotto {
 
             Manager::delete_tickets(&mut *tx).await.unwrap();
 
+            for (winner, payout) in winners.iter().copied() {
+                Manager::add_coins(&mut *tx, winner, payout).await.unwrap();
+                Ledger::coins(&mut *tx, winner, payout, LedgerSource::Lotto)
+                    .await
+                    .unwrap();
+
+                // Read after the increment, whose update holds the row lock
+                // until the transaction ends
+                let mut row = Manager::row(&mut *tx, winner)
+                    .await
+                    .unwrap()
+                    .unwrap_or_else(|| LottoRow::new(winner));
+
+                Dispatch::<Db, EventHandler>::new(&mut *tx)
+                    .fire(
+                        &mut row,
+                        Event::LottoWin(LottoWinEvent::new(winner, payout)),
+                    )
+                    .await
+                    .unwrap();
+
+                Manager::save(&mut *tx, row).await.unwrap();
+            }
+
+            tx.commit().await.unwrap();
+
             let mut lines = Vec::with_capacity(expected_winners);
 
             for (winner, payout) in winners {
-                Manager::add_coins(&mut *tx, winner, payout).await.unwrap();
-
                 let line = format!(
                     "{} ({}) has won {} <:coin:{COIN}> from the lottery!",
                     winner.mention(),
@@ -124,8 +188,6 @@ impl Lotto {
                 lines.push(line);
             }
 
-            tx.commit().await.unwrap();
-
             let embed = CreateEmbed::new()
                 .title(format!(
                     "<:coin:{COIN}> <:coin:{COIN}> Lottery!! <:coin:{COIN}> <:coin:{COIN}>"
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use serenity::all::UserId;
-use sqlx::{Database, Pool, any::AnyQueryResult};
+use sqlx::{Database, any::AnyQueryResult};
 
 use crate::shop::{LUCKY_CHIP, SHOP_ITEMS, ShopItem};
 
@@ -22,25 +22,29 @@ pub trait EffectsManager<Db: Database> {
 
     async fn remove_effect(conn: &mut Db::Connection, id: i32) -> sqlx::Result<AnyQueryResult>;
 
+    /// Applies and consumes the active effects. Runs on the caller's
+    /// transaction so it commits together with the balance change.
+    ///
+    /// Returns the final payout and the item ids of the consumed effects.
     async fn payout(
-        pool: &Pool<Db>,
+        conn: &mut Db::Connection,
         user_id: impl Into<UserId> + Send,
         bet: i64,
         mut payout: i64,
         win: bool,
-    ) -> i64 {
+    ) -> (i64, Vec<String>) {
         let base_payout = payout;
         payout = 0;
 
         let user_id = user_id.into();
 
-        let mut tx = pool.begin().await.unwrap();
-        let mut effects = Self::get_effects(&mut *tx, user_id).await.unwrap();
+        let mut effects = Self::get_effects(conn, user_id).await.unwrap();
+        let consumed = effects.keys().cloned().collect();
 
         {
             let lucky_chip = effects.remove(LUCKY_CHIP.id);
             if let Some(id) = lucky_chip {
-                Self::remove_effect(&mut *tx, id).await.unwrap();
+                Self::remove_effect(conn, id).await.unwrap();
 
                 if !win {
                     payout = bet;
@@ -49,7 +53,7 @@ pub trait EffectsManager<Db: Database> {
         }
 
         for (item_id, id) in effects.drain() {
-            Self::remove_effect(&mut *tx, id).await.unwrap();
+            Self::remove_effect(conn, id).await.unwrap();
 
             let item = SHOP_ITEMS.get(&item_id).unwrap();
 
@@ -59,9 +63,7 @@ pub trait EffectsManager<Db: Database> {
             }
         }
 
-        tx.commit().await.unwrap();
-
-        payout.max(base_payout)
+        (payout.max(base_payout), consumed)
     }
 }
 
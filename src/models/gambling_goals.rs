@@ -1,27 +1,125 @@
-use chrono::{NaiveDate, Utc};
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveTime, Utc};
 use serenity::all::UserId;
 use sqlx::FromRow;
 use zayden_core::FormatNum;
 
 use crate::goals::GOAL_REGISTRY;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GoalPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl GoalPeriod {
+    pub const ALL: [GoalPeriod; 3] = [Self::Daily, Self::Weekly, Self::Monthly];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Daily => "Daily Goals",
+            Self::Weekly => "Weekly Goals",
+            Self::Monthly => "Monthly Goals",
+        }
+    }
+
+    /// The first day of the period that `today` falls in.
+    pub fn start(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Daily => today,
+            Self::Weekly => today - Days::new(today.weekday().num_days_from_monday() as u64),
+            Self::Monthly => today.with_day(1).unwrap(),
+        }
+    }
+
+    /// Timestamp of the next reset.
+    pub fn next_reset(&self) -> i64 {
+        let start = self.start(Utc::now().date_naive());
+
+        let next = match self {
+            Self::Daily => start + Days::new(1),
+            Self::Weekly => start + Days::new(7),
+            Self::Monthly => start + Months::new(1),
+        };
+
+        next.and_time(NaiveTime::MIN).and_utc().timestamp()
+    }
+
+    pub fn goal_count(&self) -> usize {
+        3
+    }
+
+    /// Multiplier applied to the daily target of a goal.
+    pub fn target_multiplier(&self) -> i64 {
+        match self {
+            Self::Daily => 1,
+            Self::Weekly => 5,
+            Self::Monthly => 20,
+        }
+    }
+
+    /// Coins for completing each goal.
+    pub fn coin_reward(&self) -> i64 {
+        match self {
+            Self::Daily => 5_000,
+            Self::Weekly => 25_000,
+            Self::Monthly => 100_000,
+        }
+    }
+
+    /// Gems for completing all goals of the period.
+    pub fn gem_reward(&self) -> i64 {
+        match self {
+            Self::Daily => 1,
+            Self::Weekly => 3,
+            Self::Monthly => 10,
+        }
+    }
+}
+
+impl From<&str> for GoalPeriod {
+    fn from(value: &str) -> Self {
+        match value {
+            "weekly" => Self::Weekly,
+            "monthly" => Self::Monthly,
+            _ => Self::Daily,
+        }
+    }
+}
+
 #[derive(FromRow)]
 pub struct GamblingGoalsRow {
     pub user_id: i64,
     pub goal_id: String,
+    pub period: String,
+    /// The first day of the period the goal was set for
     pub day: NaiveDate,
     pub progress: i64,
     pub target: i64,
 }
 
 impl GamblingGoalsRow {
-    pub fn new(user_id: impl Into<UserId>, goal_id: impl Into<String>, target: i64) -> Self {
+    pub fn new(
+        user_id: impl Into<UserId>,
+        goal_id: impl Into<String>,
+        period: GoalPeriod,
+        target: i64,
+    ) -> Self {
         let user_id = user_id.into();
 
         Self {
             user_id: user_id.get() as i64,
             goal_id: goal_id.into(),
-            day: Utc::now().date_naive(),
+            period: period.as_str().to_string(),
+            day: period.start(Utc::now().date_naive()),
             progress: 0,
             target,
         }
@@ -31,8 +129,13 @@ impl GamblingGoalsRow {
         &self.goal_id
     }
 
-    pub fn is_today(&self) -> bool {
-        self.day == Utc::now().date_naive()
+    pub fn period(&self) -> GoalPeriod {
+        GoalPeriod::from(self.period.as_str())
+    }
+
+    pub fn is_current(&self) -> bool {
+        let period = self.period();
+        self.day == period.start(Utc::now().date_naive())
     }
 
     pub fn update_progress(&mut self, value: i64) {
@@ -1,16 +1,22 @@
 use async_trait::async_trait;
 use serenity::all::UserId;
-use sqlx::{Database, FromRow, Pool, any::AnyQueryResult};
+use sqlx::{Database, FromRow, any::AnyQueryResult};
 
 use crate::Prestige;
 
 use super::{Coins, Gems, MaxBet};
 
+/// Balance changes go through a transaction: `row` should lock the row it
+/// reads (e.g. `SELECT ... FOR UPDATE`) so concurrent commands wait for the
+/// `save` to be committed instead of overwriting each other's changes.
 #[async_trait]
 pub trait GameManager<Db: Database> {
-    async fn row(pool: &Pool<Db>, id: impl Into<UserId> + Send) -> sqlx::Result<Option<GameRow>>;
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+    ) -> sqlx::Result<Option<GameRow>>;
 
-    async fn save(pool: &Pool<Db>, row: GameRow) -> sqlx::Result<AnyQueryResult>;
+    async fn save(conn: &mut Db::Connection, row: GameRow) -> sqlx::Result<AnyQueryResult>;
 }
 
 #[derive(FromRow)]
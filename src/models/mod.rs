@@ -7,7 +7,7 @@ mod game_row;
 
 use chrono::{NaiveDateTime, Timelike, Utc};
 pub use gambling_effects::{EffectsManager, EffectsRow};
-pub use gambling_goals::GamblingGoalsRow;
+pub use gambling_goals::{GamblingGoalsRow, GoalPeriod};
 pub use gambling_item::GamblingItem;
 pub use game_row::{GameManager, GameRow};
 use sqlx::Database;
@@ -0,0 +1,52 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use serenity::all::{Mentionable, UserId};
+use sqlx::Database;
+use sqlx::any::AnyQueryResult;
+use zayden_core::FormatNum;
+
+use crate::COIN;
+use crate::events::{Event, EventRow, Subscriber};
+
+#[async_trait]
+pub trait NotificationsManager<Db: Database> {
+    /// Queues a direct message for the user. Subscribers have no access to
+    /// Discord, so the bot delivers the queue after the transaction commits.
+    async fn push(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+        content: String,
+    ) -> sqlx::Result<AnyQueryResult>;
+}
+
+/// Lets players know about the coins other players or the lottery gave them.
+pub struct NotificationsSubscriber<Manager>(PhantomData<Manager>);
+
+#[async_trait]
+impl<Db: Database, Manager: NotificationsManager<Db>> Subscriber<Db>
+    for NotificationsSubscriber<Manager>
+{
+    async fn on_event(
+        conn: &mut Db::Connection,
+        _row: &mut dyn EventRow,
+        event: &Event,
+    ) -> sqlx::Result<()> {
+        let content = match event {
+            Event::Receive(event) => format!(
+                "{} sent you {} <:coin:{COIN}>",
+                event.sender.mention(),
+                event.amount.format()
+            ),
+            Event::LottoWin(event) => format!(
+                "You won {} <:coin:{COIN}> from the lottery!",
+                event.payout.format()
+            ),
+            _ => return Ok(()),
+        };
+
+        Manager::push(conn, event.user_id(), content).await?;
+
+        Ok(())
+    }
+}
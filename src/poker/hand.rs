@@ -0,0 +1,194 @@
+use std::fmt::Display;
+
+use serenity::all::EmojiId;
+
+use crate::CARD_DECK;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub emoji: EmojiId,
+    pub rank: u8,
+    pub suit: u8,
+}
+
+impl From<EmojiId> for Card {
+    fn from(emoji: EmojiId) -> Self {
+        let index = CARD_DECK.iter().position(|card| *card == emoji).unwrap();
+
+        let rank = match index % 13 {
+            0 => 14,
+            r => r as u8 + 1,
+        };
+
+        Self {
+            emoji,
+            rank,
+            suit: (index / 13) as u8,
+        }
+    }
+}
+
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = if self.rank == 14 { 1 } else { self.rank };
+        write!(f, "<:{name}:{}>", self.emoji)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HighCard => write!(f, "High Card"),
+            Self::OnePair => write!(f, "Pair"),
+            Self::TwoPair => write!(f, "Two Pair"),
+            Self::ThreeOfAKind => write!(f, "Three of a Kind"),
+            Self::Straight => write!(f, "Straight"),
+            Self::Flush => write!(f, "Flush"),
+            Self::FullHouse => write!(f, "Full House"),
+            Self::FourOfAKind => write!(f, "Four of a Kind"),
+            Self::StraightFlush => write!(f, "Straight Flush"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandRank {
+    pub category: Category,
+    kickers: [u8; 5],
+}
+
+pub fn evaluate(cards: &[Card]) -> HandRank {
+    (0u32..1 << cards.len())
+        .filter(|mask| mask.count_ones() == 5)
+        .map(|mask| {
+            let hand = cards
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, card)| *card)
+                .collect::<Vec<_>>();
+
+            evaluate_five(&hand)
+        })
+        .max()
+        .expect("at least 5 cards are required")
+}
+
+fn evaluate_five(cards: &[Card]) -> HandRank {
+    let mut ranks = cards.iter().map(|card| card.rank).collect::<Vec<_>>();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+
+    let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+
+    let straight_high = if ranks == [14, 5, 4, 3, 2] {
+        Some(5)
+    } else if ranks.windows(2).all(|w| w[0] == w[1] + 1) {
+        Some(ranks[0])
+    } else {
+        None
+    };
+
+    // (count, rank) sorted so the largest groups come first, then the highest rank
+    let mut groups: Vec<(u8, u8)> = Vec::with_capacity(5);
+    for rank in ranks {
+        match groups.iter_mut().find(|(_, r)| *r == rank) {
+            Some((count, _)) => *count += 1,
+            None => groups.push((1, rank)),
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+
+    let category = match (
+        straight_high,
+        flush,
+        groups[0].0,
+        groups.get(1).map(|g| g.0),
+    ) {
+        (Some(_), true, _, _) => Category::StraightFlush,
+        (_, _, 4, _) => Category::FourOfAKind,
+        (_, _, 3, Some(2)) => Category::FullHouse,
+        (_, true, _, _) => Category::Flush,
+        (Some(_), _, _, _) => Category::Straight,
+        (_, _, 3, _) => Category::ThreeOfAKind,
+        (_, _, 2, Some(2)) => Category::TwoPair,
+        (_, _, 2, _) => Category::OnePair,
+        _ => Category::HighCard,
+    };
+
+    let mut kickers = [0; 5];
+
+    match straight_high {
+        Some(high) if matches!(category, Category::Straight | Category::StraightFlush) => {
+            kickers[0] = high
+        }
+        _ => groups
+            .iter()
+            .map(|(_, rank)| *rank)
+            .zip(kickers.iter_mut())
+            .for_each(|(rank, kicker)| *kicker = rank),
+    }
+
+    HandRank { category, kickers }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(cards: &[(u8, u8)]) -> Vec<Card> {
+        cards
+            .iter()
+            .map(|&(rank, suit)| Card {
+                emoji: EmojiId::new(1),
+                rank,
+                suit,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wheel_is_the_lowest_straight() {
+        let wheel = evaluate(&hand(&[(14, 0), (2, 1), (3, 2), (4, 3), (5, 0)]));
+        let six_high = evaluate(&hand(&[(2, 1), (3, 2), (4, 3), (5, 0), (6, 1)]));
+
+        assert!(wheel.category == Category::Straight);
+        assert!(wheel < six_high);
+    }
+
+    #[test]
+    fn best_five_of_seven() {
+        let rank = evaluate(&hand(&[
+            (9, 0),
+            (9, 1),
+            (2, 0),
+            (7, 0),
+            (11, 0),
+            (13, 2),
+            (4, 0),
+        ]));
+
+        assert!(rank.category == Category::Flush);
+    }
+
+    #[test]
+    fn kickers_break_ties() {
+        let ace_kicker = evaluate(&hand(&[(8, 0), (8, 1), (14, 2), (6, 3), (3, 0)]));
+        let king_kicker = evaluate(&hand(&[(8, 2), (8, 3), (13, 2), (6, 1), (3, 1)]));
+
+        assert!(ace_kicker.category == Category::OnePair);
+        assert!(ace_kicker > king_kicker);
+    }
+}
@@ -0,0 +1,5 @@
+mod hand;
+mod table;
+
+pub use hand::{Card, Category, HandRank, evaluate};
+pub use table::{Action, Seat, Stage, Table};
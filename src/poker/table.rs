@@ -0,0 +1,411 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::rng;
+use rand::seq::SliceRandom;
+use serenity::all::UserId;
+
+use crate::CARD_DECK;
+
+use super::{Card, HandRank, evaluate};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+    Showdown,
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PreFlop => write!(f, "Pre-Flop"),
+            Self::Flop => write!(f, "Flop"),
+            Self::Turn => write!(f, "Turn"),
+            Self::River => write!(f, "River"),
+            Self::Showdown => write!(f, "Showdown"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Action {
+    Fold,
+    Call,
+    Raise,
+    AllIn,
+}
+
+impl FromStr for Action {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "poker_fold" => Ok(Self::Fold),
+            "poker_call" => Ok(Self::Call),
+            "poker_raise" => Ok(Self::Raise),
+            "poker_allin" => Ok(Self::AllIn),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct Seat {
+    pub user_id: UserId,
+    pub stack: i64,
+    pub bet: i64,
+    pub contributed: i64,
+    pub hole: [Card; 2],
+    pub folded: bool,
+    acted: bool,
+}
+
+impl Seat {
+    fn put(&mut self, amount: i64) {
+        let amount = amount.min(self.stack);
+
+        self.stack -= amount;
+        self.bet += amount;
+        self.contributed += amount;
+    }
+
+    pub fn is_all_in(&self) -> bool {
+        !self.folded && self.stack == 0
+    }
+
+    fn can_act(&self) -> bool {
+        !self.folded && self.stack > 0
+    }
+}
+
+pub struct Table {
+    pub buy_in: i64,
+    pub big_blind: i64,
+    pub seats: Vec<Seat>,
+    pub community: Vec<Card>,
+    pub stage: Stage,
+    pub current: usize,
+    pub current_bet: i64,
+    min_raise: i64,
+    deck: Vec<Card>,
+}
+
+impl Table {
+    pub fn new(buy_in: i64, players: Vec<UserId>) -> Self {
+        let mut deck = CARD_DECK
+            .iter()
+            .copied()
+            .map(Card::from)
+            .collect::<Vec<_>>();
+        deck.shuffle(&mut rng());
+
+        let big_blind = Self::big_blind_for(buy_in);
+
+        let seats = players
+            .into_iter()
+            .map(|user_id| Seat {
+                user_id,
+                stack: buy_in,
+                bet: 0,
+                contributed: 0,
+                hole: [deck.pop().unwrap(), deck.pop().unwrap()],
+                folded: false,
+                acted: false,
+            })
+            .collect::<Vec<_>>();
+
+        // Seat 0 holds the dealer button. Heads-up the dealer posts the small blind.
+        let n = seats.len();
+        let (sb, bb) = if n == 2 { (0, 1) } else { (1, 2) };
+
+        let mut table = Self {
+            buy_in,
+            big_blind,
+            seats,
+            community: Vec::with_capacity(5),
+            stage: Stage::PreFlop,
+            current: (bb + 1) % n,
+            current_bet: big_blind,
+            min_raise: big_blind,
+            deck,
+        };
+
+        table.seats[sb].put(big_blind / 2);
+        table.seats[bb].put(big_blind);
+
+        if !table.seats[table.current].can_act() {
+            table.current = table.next_to_act(table.current);
+        }
+
+        table
+    }
+
+    pub fn big_blind_for(buy_in: i64) -> i64 {
+        (buy_in / 20).max(2)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.stage == Stage::Showdown
+    }
+
+    pub fn current_player(&self) -> UserId {
+        self.seats[self.current].user_id
+    }
+
+    pub fn seat(&self, user_id: UserId) -> Option<&Seat> {
+        self.seats.iter().find(|seat| seat.user_id == user_id)
+    }
+
+    pub fn pot(&self) -> i64 {
+        self.seats.iter().map(|seat| seat.contributed).sum()
+    }
+
+    pub fn to_call(&self) -> i64 {
+        let seat = &self.seats[self.current];
+        (self.current_bet - seat.bet).min(seat.stack)
+    }
+
+    pub fn min_raise_to(&self) -> i64 {
+        self.current_bet + self.min_raise
+    }
+
+    pub fn can_raise(&self) -> bool {
+        let seat = &self.seats[self.current];
+        self.min_raise_to() - seat.bet < seat.stack
+    }
+
+    pub fn timeout_action(&self) -> Action {
+        if self.to_call() == 0 {
+            Action::Call
+        } else {
+            Action::Fold
+        }
+    }
+
+    pub fn apply(&mut self, action: Action) {
+        let current_bet = self.current_bet;
+        let seat = &mut self.seats[self.current];
+
+        match action {
+            Action::Fold => seat.folded = true,
+            Action::Call => seat.put(current_bet - seat.bet),
+            Action::Raise => seat.put(current_bet + self.min_raise - seat.bet),
+            Action::AllIn => seat.put(seat.stack),
+        }
+
+        seat.acted = true;
+
+        if seat.bet > current_bet {
+            self.min_raise = self.min_raise.max(seat.bet - current_bet);
+            self.current_bet = seat.bet;
+
+            let current = self.current;
+            self.seats
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, _)| *i != current)
+                .for_each(|(_, seat)| seat.acted = false);
+        }
+
+        self.advance();
+    }
+
+    fn remaining(&self) -> usize {
+        self.seats.iter().filter(|seat| !seat.folded).count()
+    }
+
+    fn next_to_act(&self, from: usize) -> usize {
+        let n = self.seats.len();
+
+        (1..=n)
+            .map(|i| (from + i) % n)
+            .find(|i| self.seats[*i].can_act())
+            .unwrap_or(from)
+    }
+
+    fn advance(&mut self) {
+        if self.remaining() == 1 {
+            self.stage = Stage::Showdown;
+            return;
+        }
+
+        let round_over = self
+            .seats
+            .iter()
+            .filter(|seat| seat.can_act())
+            .all(|seat| seat.acted && seat.bet == self.current_bet);
+
+        if round_over {
+            self.next_stage();
+        } else {
+            self.current = self.next_to_act(self.current);
+        }
+    }
+
+    fn next_stage(&mut self) {
+        loop {
+            self.seats.iter_mut().for_each(|seat| {
+                seat.bet = 0;
+                seat.acted = false;
+            });
+            self.current_bet = 0;
+            self.min_raise = self.big_blind;
+
+            let (stage, cards) = match self.stage {
+                Stage::PreFlop => (Stage::Flop, 3),
+                Stage::Flop => (Stage::Turn, 1),
+                Stage::Turn => (Stage::River, 1),
+                Stage::River | Stage::Showdown => (Stage::Showdown, 0),
+            };
+
+            for _ in 0..cards {
+                let card = self.deck.pop().unwrap();
+                self.community.push(card);
+            }
+
+            self.stage = stage;
+
+            if self.stage == Stage::Showdown {
+                return;
+            }
+
+            // Keep dealing until showdown when at most one player is not all in
+            if self.seats.iter().filter(|seat| seat.can_act()).count() >= 2 {
+                self.current = self.next_to_act(0);
+                return;
+            }
+        }
+    }
+
+    pub fn hand_rank(&self, seat: &Seat) -> HandRank {
+        let cards = seat
+            .hole
+            .iter()
+            .chain(self.community.iter())
+            .copied()
+            .collect::<Vec<_>>();
+
+        evaluate(&cards)
+    }
+
+    pub fn showdown(&self) -> Vec<Option<HandRank>> {
+        let contested = self.remaining() > 1;
+
+        self.seats
+            .iter()
+            .map(|seat| (contested && !seat.folded).then(|| self.hand_rank(seat)))
+            .collect()
+    }
+
+    /// Splits the main pot and any side pots between the best hands and
+    /// returns the amount each seat won. Winnings are added to the stacks.
+    pub fn settle(&mut self) -> Vec<i64> {
+        let ranks = self.showdown();
+
+        let mut levels = self
+            .seats
+            .iter()
+            .map(|seat| seat.contributed)
+            .filter(|contributed| *contributed > 0)
+            .collect::<Vec<_>>();
+        levels.sort_unstable();
+        levels.dedup();
+
+        let mut winnings = vec![0; self.seats.len()];
+        let mut prev = 0;
+
+        for level in levels {
+            let pot = self
+                .seats
+                .iter()
+                .map(|seat| seat.contributed.min(level) - seat.contributed.min(prev))
+                .sum::<i64>();
+
+            let mut eligible = (0..self.seats.len())
+                .filter(|i| !self.seats[*i].folded && self.seats[*i].contributed >= level)
+                .collect::<Vec<_>>();
+
+            if eligible.is_empty() {
+                eligible = (0..self.seats.len())
+                    .filter(|i| !self.seats[*i].folded)
+                    .collect();
+            }
+
+            let best = eligible.iter().filter_map(|i| ranks[*i]).max();
+
+            let winners = eligible
+                .into_iter()
+                .filter(|i| ranks[*i] == best)
+                .collect::<Vec<_>>();
+
+            let share = pot / winners.len() as i64;
+            let remainder = pot % winners.len() as i64;
+
+            for (n, i) in winners.iter().enumerate() {
+                winnings[*i] += share + if n == 0 { remainder } else { 0 };
+            }
+
+            prev = level;
+        }
+
+        self.seats
+            .iter_mut()
+            .zip(winnings.iter())
+            .for_each(|(seat, won)| seat.stack += won);
+
+        winnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::EmojiId;
+
+    use super::*;
+
+    fn card(rank: u8, suit: u8) -> Card {
+        Card {
+            emoji: EmojiId::new(1),
+            rank,
+            suit,
+        }
+    }
+
+    fn seat(id: u64, contributed: i64, hole: [Card; 2]) -> Seat {
+        Seat {
+            user_id: UserId::new(id),
+            stack: 0,
+            bet: 0,
+            contributed,
+            hole,
+            folded: false,
+            acted: true,
+        }
+    }
+
+    #[test]
+    fn short_stack_only_wins_the_main_pot() {
+        let mut table = Table {
+            buy_in: 300,
+            big_blind: 10,
+            seats: vec![
+                // Trips, all in for 100
+                seat(1, 100, [card(12, 0), card(12, 1)]),
+                // Pair of kings
+                seat(2, 300, [card(13, 0), card(3, 1)]),
+                // Pair of fours
+                seat(3, 300, [card(4, 0), card(4, 1)]),
+            ],
+            community: vec![card(12, 2), card(13, 3), card(7, 0), card(2, 1), card(9, 3)],
+            stage: Stage::Showdown,
+            current: 0,
+            current_bet: 0,
+            min_raise: 10,
+            deck: Vec::new(),
+        };
+
+        assert_eq!(table.settle(), vec![300, 400, 0]);
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serenity::all::{
+    ChannelId, Colour, Context, CreateEmbed, EditMessage, Message, MessageId, UserId,
+};
+use serenity::prelude::TypeMapKey;
+use sqlx::any::AnyQueryResult;
+use sqlx::types::Json;
+use sqlx::{Database, FromRow, Pool};
+use zayden_core::FormatNum;
+
+use crate::commands::higher_lower::HigherLowerSession;
+use crate::commands::history::HistorySession;
+use crate::commands::leaderboard::LeaderboardSession;
+use crate::{COIN, Coins, GameManager, GameRow, LedgerManager, LedgerSource, Result};
+
+#[async_trait]
+pub trait SessionManager<Db: Database> {
+    async fn rows(pool: &Pool<Db>) -> sqlx::Result<Vec<SessionRow>>;
+
+    /// Replaces the row with the same id. Deleted on the same transaction as
+    /// the payouts, so a settled game is never refunded on recovery.
+    async fn save(conn: &mut Db::Connection, row: SessionRow) -> sqlx::Result<AnyQueryResult>;
+
+    async fn delete(
+        conn: &mut Db::Connection,
+        id: impl Into<MessageId> + Send,
+    ) -> sqlx::Result<AnyQueryResult>;
+}
+
+/// An open interactive session, keyed by the id of the message holding the
+/// components, along with the coins taken from each player for it.
+#[derive(FromRow)]
+pub struct SessionRow {
+    pub id: i64,
+    pub channel_id: i64,
+    pub game_id: String,
+    pub stakes: Json<Vec<Stake>>,
+}
+
+impl SessionRow {
+    pub fn new(game_id: impl Into<String>, msg: &Message) -> Self {
+        Self {
+            id: msg.id.get() as i64,
+            channel_id: msg.channel_id.get() as i64,
+            game_id: game_id.into(),
+            stakes: Json(Vec::new()),
+        }
+    }
+
+    pub fn stake(mut self, user_id: impl Into<UserId>, amount: i64) -> Self {
+        self.stakes.push(Stake {
+            user_id: user_id.into().get() as i64,
+            amount,
+        });
+        self
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct Stake {
+    pub user_id: i64,
+    pub amount: i64,
+}
+
+pub enum Session {
+    HigherLower(HigherLowerSession),
+    Leaderboard(LeaderboardSession),
+    History(HistorySession),
+}
+
+/// State that an interactive command keeps between component interactions.
+pub trait SessionState:
+    Into<Session> + TryFrom<Session, Error = Session> + Send + Sync + 'static
+{
+    fn from_session(session: &mut Session) -> Option<&mut Self>;
+}
+
+impl From<HigherLowerSession> for Session {
+    fn from(value: HigherLowerSession) -> Self {
+        Self::HigherLower(value)
+    }
+}
+
+impl TryFrom<Session> for HigherLowerSession {
+    type Error = Session;
+
+    fn try_from(value: Session) -> std::result::Result<Self, Self::Error> {
+        match value {
+            Session::HigherLower(state) => Ok(state),
+            session => Err(session),
+        }
+    }
+}
+
+impl SessionState for HigherLowerSession {
+    fn from_session(session: &mut Session) -> Option<&mut Self> {
+        match session {
+            Session::HigherLower(state) => Some(state),
+            _ => None,
+        }
+    }
+}
+
+impl From<LeaderboardSession> for Session {
+    fn from(value: LeaderboardSession) -> Self {
+        Self::Leaderboard(value)
+    }
+}
+
+impl TryFrom<Session> for LeaderboardSession {
+    type Error = Session;
+
+    fn try_from(value: Session) -> std::result::Result<Self, Self::Error> {
+        match value {
+            Session::Leaderboard(state) => Ok(state),
+            session => Err(session),
+        }
+    }
+}
+
+impl SessionState for LeaderboardSession {
+    fn from_session(session: &mut Session) -> Option<&mut Self> {
+        match session {
+            Session::Leaderboard(state) => Some(state),
+            _ => None,
+        }
+    }
+}
+
+impl From<HistorySession> for Session {
+    fn from(value: HistorySession) -> Self {
+        Self::History(value)
+    }
+}
+
+impl TryFrom<Session> for HistorySession {
+    type Error = Session;
+
+    fn try_from(value: Session) -> std::result::Result<Self, Self::Error> {
+        match value {
+            Session::History(state) => Ok(state),
+            session => Err(session),
+        }
+    }
+}
+
+impl SessionState for HistorySession {
+    fn from_session(session: &mut Session) -> Option<&mut Self> {
+        match session {
+            Session::History(state) => Some(state),
+            _ => None,
+        }
+    }
+}
+
+/// Session state of the interactive commands, keyed by the id of the message
+/// holding the components.
+pub struct SessionStore(HashMap<MessageId, Session>);
+
+impl SessionStore {
+    pub async fn insert(ctx: &Context, id: impl Into<MessageId>, session: impl SessionState) {
+        let mut data = ctx.data.write().await;
+        let store = data
+            .entry::<SessionStore>()
+            .or_insert(SessionStore(HashMap::new()));
+
+        store.0.insert(id.into(), session.into());
+    }
+
+    /// Runs `f` on the session of this message if it holds a `T`.
+    pub async fn update<T: SessionState, R>(
+        ctx: &Context,
+        id: impl Into<MessageId>,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
+        let mut data = ctx.data.write().await;
+        let store = data.get_mut::<SessionStore>()?;
+
+        store.0.get_mut(&id.into()).and_then(T::from_session).map(f)
+    }
+
+    pub async fn remove<T: SessionState>(ctx: &Context, id: impl Into<MessageId>) -> Option<T> {
+        let mut data = ctx.data.write().await;
+        let store = data.get_mut::<SessionStore>()?;
+
+        let id = id.into();
+        let session = store.0.remove(&id)?;
+
+        match T::try_from(session) {
+            Ok(state) => Some(state),
+            Err(session) => {
+                store.0.insert(id, session);
+                None
+            }
+        }
+    }
+
+    /// Refunds every stake of the sessions left open by the last run, as their
+    /// component collectors died with it, and closes their messages.
+    /// Should be called once on startup.
+    pub async fn recover<
+        Db: Database,
+        SessionHandler: SessionManager<Db>,
+        GameHandler: GameManager<Db>,
+        LedgerHandler: LedgerManager<Db>,
+    >(
+        ctx: &Context,
+        pool: &Pool<Db>,
+    ) -> Result<()> {
+        for session in SessionHandler::rows(pool).await? {
+            let mut refunds = Vec::new();
+            let mut tx = pool.begin().await?;
+
+            for stake in session.stakes.iter() {
+                let mut row = GameHandler::row(&mut *tx, stake.user_id as u64)
+                    .await?
+                    .unwrap_or_else(|| GameRow::new(stake.user_id as u64));
+
+                row.add_coins(stake.amount);
+                LedgerHandler::coins(
+                    &mut *tx,
+                    stake.user_id as u64,
+                    stake.amount,
+                    LedgerSource::Refund(&session.game_id),
+                )
+                .await?;
+
+                GameHandler::save(&mut *tx, row).await?;
+
+                refunds.push(format!(
+                    "<@{}>: {} <:coin:{COIN}>",
+                    stake.user_id,
+                    stake.amount.format()
+                ));
+            }
+
+            SessionHandler::delete(&mut *tx, session.id as u64).await?;
+            tx.commit().await?;
+
+            let mut desc = String::from("This game was interrupted by a restart.");
+
+            if !refunds.is_empty() {
+                desc.push_str("\n\nRefunded:\n");
+                desc.push_str(&refunds.join("\n"));
+            }
+
+            let embed = CreateEmbed::new().description(desc).colour(Colour::ORANGE);
+
+            // The message may have been deleted in the meantime
+            let _ = ChannelId::new(session.channel_id as u64)
+                .edit_message(
+                    ctx,
+                    MessageId::new(session.id as u64),
+                    EditMessage::new().embed(embed).components(Vec::new()),
+                )
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+impl TypeMapKey for SessionStore {
+    type Value = SessionStore;
+}
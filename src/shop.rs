@@ -508,7 +508,7 @@ const GALAXY: ShopItem = ShopItem::new(
 .add_cost(25000, ShopCurrency::Utility)
 .add_cost(10000, ShopCurrency::Production);
 
-const UNIVERSE: ShopItem = ShopItem::new(
+pub const UNIVERSE: ShopItem = ShopItem::new(
     "universe",
     "Universe",
     Emoji::None,
@@ -0,0 +1,104 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use serenity::all::UserId;
+use sqlx::any::AnyQueryResult;
+use sqlx::{Database, FromRow, Pool};
+
+use crate::events::{Event, EventRow, GameEvent, Subscriber};
+
+#[async_trait]
+pub trait StatsManager<Db: Database> {
+    async fn row(
+        conn: &mut Db::Connection,
+        id: impl Into<UserId> + Send,
+        game_id: &str,
+    ) -> sqlx::Result<Option<GameStatsRow>>;
+
+    async fn rows(pool: &Pool<Db>, id: impl Into<UserId> + Send)
+    -> sqlx::Result<Vec<GameStatsRow>>;
+
+    async fn save(conn: &mut Db::Connection, row: GameStatsRow) -> sqlx::Result<AnyQueryResult>;
+
+    async fn record(conn: &mut Db::Connection, event: &GameEvent) -> sqlx::Result<()> {
+        let mut row = Self::row(conn, event.user_id, &event.game_id)
+            .await?
+            .unwrap_or_else(|| GameStatsRow::new(event.user_id, &event.game_id));
+
+        row.update(event);
+
+        Self::save(conn, row).await?;
+
+        Ok(())
+    }
+}
+
+pub struct StatsSubscriber<Manager>(PhantomData<Manager>);
+
+#[async_trait]
+impl<Db: Database, Manager: StatsManager<Db>> Subscriber<Db> for StatsSubscriber<Manager> {
+    async fn on_event(
+        conn: &mut Db::Connection,
+        _row: &mut dyn EventRow,
+        event: &Event,
+    ) -> sqlx::Result<()> {
+        if let Event::Game(game) = event {
+            Manager::record(conn, game).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Lifetime statistics of a user for a single game.
+#[derive(FromRow)]
+pub struct GameStatsRow {
+    pub user_id: i64,
+    pub game_id: String,
+    pub played: i64,
+    pub wins: i64,
+    pub losses: i64,
+    pub wagered: i64,
+    pub won: i64,
+    pub biggest_win: i64,
+    pub streak: i64,
+    pub longest_streak: i64,
+}
+
+impl GameStatsRow {
+    pub fn new(user_id: impl Into<UserId>, game_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into().get() as i64,
+            game_id: game_id.into(),
+            played: 0,
+            wins: 0,
+            losses: 0,
+            wagered: 0,
+            won: 0,
+            biggest_win: 0,
+            streak: 0,
+            longest_streak: 0,
+        }
+    }
+
+    pub fn net_profit(&self) -> i64 {
+        self.won - self.wagered
+    }
+
+    pub fn update(&mut self, event: &GameEvent) {
+        self.played += 1;
+        self.wagered += event.bet;
+        self.won += event.payout;
+
+        // Draws neither extend nor break a winning streak
+        if event.win {
+            self.wins += 1;
+            self.streak += 1;
+            self.longest_streak = self.longest_streak.max(self.streak);
+            self.biggest_win = self.biggest_win.max(event.payout - event.bet);
+        } else if !event.draw {
+            self.losses += 1;
+            self.streak = 0;
+        }
+    }
+}
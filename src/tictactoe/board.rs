@@ -0,0 +1,158 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    X,
+    O,
+}
+
+impl Mark {
+    pub fn emoji(&self) -> char {
+        match self {
+            Self::X => '❌',
+            Self::O => '⭕',
+        }
+    }
+
+    pub fn other(&self) -> Self {
+        match self {
+            Self::X => Self::O,
+            Self::O => Self::X,
+        }
+    }
+}
+
+// Right, down, down-right and down-left. Scanning these from every cell
+// covers each line once.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+#[derive(Clone)]
+pub struct Board {
+    size: usize,
+    win_length: usize,
+    cells: Vec<Option<Mark>>,
+}
+
+impl Board {
+    /// Creates an empty `size` x `size` board. The win length is clamped to
+    /// the board size.
+    pub fn new(size: usize, win_length: usize) -> Self {
+        Self {
+            size,
+            win_length: win_length.clamp(1, size),
+            cells: vec![None; size * size],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn win_length(&self) -> usize {
+        self.win_length
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<Mark> {
+        self.cells[row * self.size + col]
+    }
+
+    pub fn available(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_none())
+            .map(|(i, _)| (i / self.size, i % self.size))
+    }
+
+    /// Places the mark if the cell is empty. Returns false if it was taken.
+    pub fn place(&mut self, row: usize, col: usize, mark: Mark) -> bool {
+        let cell = &mut self.cells[row * self.size + col];
+
+        if cell.is_some() {
+            return false;
+        }
+
+        *cell = Some(mark);
+        true
+    }
+
+    pub fn clear(&mut self, row: usize, col: usize) {
+        self.cells[row * self.size + col] = None;
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.cells.iter().all(Option::is_some)
+    }
+
+    pub fn winner(&self) -> Option<Mark> {
+        (0..self.size)
+            .flat_map(|row| (0..self.size).map(move |col| (row, col)))
+            .find_map(|(row, col)| {
+                let mark = self.get(row, col)?;
+
+                DIRECTIONS
+                    .iter()
+                    .any(|dir| self.run_length(row, col, *dir, mark) >= self.win_length)
+                    .then_some(mark)
+            })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.winner().is_some() || self.is_full()
+    }
+
+    /// Whether the mark in this cell is part of a winning line. Cheaper than
+    /// `winner` when only the last move can have changed the result.
+    pub fn completes_line(&self, row: usize, col: usize) -> bool {
+        let Some(mark) = self.get(row, col) else {
+            return false;
+        };
+
+        DIRECTIONS.iter().any(|(dr, dc)| {
+            let forward = self.run_length(row, col, (*dr, *dc), mark);
+            let backward = self.run_length(row, col, (-dr, -dc), mark);
+
+            forward + backward - 1 >= self.win_length
+        })
+    }
+
+    fn run_length(&self, row: usize, col: usize, (dr, dc): (isize, isize), mark: Mark) -> usize {
+        (0..self.win_length)
+            .take_while(|step| {
+                let r = row as isize + dr * *step as isize;
+                let c = col as isize + dc * *step as isize;
+
+                (0..self.size as isize).contains(&r)
+                    && (0..self.size as isize).contains(&c)
+                    && self.get(r as usize, c as usize) == Some(mark)
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_diagonal_wins_on_a_larger_board() {
+        let mut board = Board::new(4, 3);
+
+        for (row, col) in [(1, 3), (2, 2), (3, 1)] {
+            board.place(row, col, Mark::O);
+        }
+
+        assert!(board.winner() == Some(Mark::O));
+        assert!(board.completes_line(2, 2));
+    }
+
+    #[test]
+    fn line_shorter_than_win_length_does_not_win() {
+        let mut board = Board::new(5, 4);
+
+        for col in 0..3 {
+            board.place(0, col, Mark::X);
+        }
+
+        assert!(board.winner().is_none());
+        assert!(!board.completes_line(0, 2));
+    }
+}
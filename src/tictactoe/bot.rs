@@ -0,0 +1,157 @@
+use std::str::FromStr;
+
+use rand::seq::IteratorRandom;
+
+use super::{Board, Mark};
+
+const WIN: i32 = 1_000_000;
+
+#[derive(Clone, Copy)]
+pub enum Difficulty {
+    Easy,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Self::Easy),
+            "hard" => Ok(Self::Hard),
+            _ => Err(()),
+        }
+    }
+}
+
+pub fn best_move(board: &Board, mark: Mark, difficulty: Difficulty) -> Option<(usize, usize)> {
+    match difficulty {
+        Difficulty::Easy => board.available().choose(&mut rand::rng()),
+        Difficulty::Hard => {
+            let mut board = board.clone();
+            let depth = search_depth(board.size());
+
+            let mut best = None;
+            let mut alpha = -WIN - 1;
+
+            for (row, col) in ordered_moves(&board) {
+                board.place(row, col, mark);
+                let score = -negamax(&mut board, (row, col), depth - 1, -WIN - 1, -alpha, mark);
+                board.clear(row, col);
+
+                if best.is_none() || score > alpha {
+                    alpha = score;
+                    best = Some((row, col));
+                }
+            }
+
+            best
+        }
+    }
+}
+
+// Full search on 3x3, larger boards fall back to the heuristic once the depth
+// runs out so a move is picked well within the interaction timeout.
+fn search_depth(size: usize) -> usize {
+    match size {
+        0..=3 => 9,
+        4 => 6,
+        _ => 5,
+    }
+}
+
+/// Scores the position for the player to move, `last` being the opponent's
+/// last move with `last_mark`.
+fn negamax(
+    board: &mut Board,
+    last: (usize, usize),
+    depth: usize,
+    mut alpha: i32,
+    beta: i32,
+    last_mark: Mark,
+) -> i32 {
+    if board.completes_line(last.0, last.1) {
+        // Prefer quicker wins and slower losses
+        return -(WIN - (board.size() * board.size() - board.available().count()) as i32);
+    }
+
+    if board.is_full() {
+        return 0;
+    }
+
+    let mark = last_mark.other();
+
+    if depth == 0 {
+        return evaluate(board, mark);
+    }
+
+    let mut best = -WIN - 1;
+
+    for (row, col) in ordered_moves(board) {
+        board.place(row, col, mark);
+        let score = -negamax(board, (row, col), depth - 1, -beta, -alpha, mark);
+        board.clear(row, col);
+
+        best = best.max(score);
+        alpha = alpha.max(score);
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+// Centre cells first, they take part in the most lines and prune the most
+fn ordered_moves(board: &Board) -> Vec<(usize, usize)> {
+    let centre = (board.size() - 1) as isize;
+
+    let mut moves = board.available().collect::<Vec<_>>();
+    moves.sort_by_key(|(row, col)| {
+        (2 * *row as isize - centre).abs() + (2 * *col as isize - centre).abs()
+    });
+
+    moves
+}
+
+/// Counts every open window of `win_length` cells. Windows holding only one
+/// player's marks score exponentially in the number of marks.
+fn evaluate(board: &Board, mark: Mark) -> i32 {
+    let size = board.size() as isize;
+    let length = board.win_length() as isize;
+
+    let mut score = 0;
+
+    for row in 0..size {
+        for col in 0..size {
+            for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                let end_row = row + dr * (length - 1);
+                let end_col = col + dc * (length - 1);
+
+                if !(0..size).contains(&end_row) || !(0..size).contains(&end_col) {
+                    continue;
+                }
+
+                let (mut own, mut other) = (0, 0);
+
+                for step in 0..length {
+                    match board.get((row + dr * step) as usize, (col + dc * step) as usize) {
+                        Some(m) if m == mark => own += 1,
+                        Some(_) => other += 1,
+                        None => {}
+                    }
+                }
+
+                match (own, other) {
+                    (0, 0) => {}
+                    (own, 0) => score += 10i32.pow(own),
+                    (0, other) => score -= 10i32.pow(other),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    score
+}
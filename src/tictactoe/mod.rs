@@ -0,0 +1,5 @@
+mod board;
+mod bot;
+
+pub use board::{Board, Mark};
+pub use bot::{Difficulty, best_move};
@@ -62,6 +62,12 @@ impl PartialEq for GameResult<'_> {
     }
 }
 
+/// A change in coins with its sign, e.g. `+1,500` or `-200`.
+pub fn signed(delta: i64) -> String {
+    let sign = if delta >= 0 { "+" } else { "" };
+    format!("{sign}{}", delta.format())
+}
+
 pub fn game_embed<'a>(
     title: impl Into<String>,
     prediction: impl Into<GameResult<'a>>,
@@ -76,11 +82,7 @@ pub fn game_embed<'a>(
 
     let win = prediction == outcome;
 
-    let result = format!(
-        "Payout: {} ({:+})",
-        payout.format(),
-        (payout - bet).format()
-    );
+    let result = format!("Payout: {} ({})", payout.format(), signed(payout - bet));
 
     let colour = if win { Colour::DARK_GREEN } else { Colour::RED };
 